@@ -0,0 +1,76 @@
+//! Software rasterizer benchmarks: `cargo bench` (add `--features simd,parallel`
+//! to compare the accelerated paths)
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pixel_engine::scratch::Scratch;
+use pixel_engine::{PixelBuffer, Rect, UploadState};
+
+const W: u32 = 640;
+const H: u32 = 360;
+
+/// 32x32 sprite with an opaque middle, soft edge and transparent corners
+fn sprite() -> Vec<[u8; 4]> {
+    (0..32 * 32)
+        .map(|i: i32| {
+            let (x, y) = (i % 32 - 16, i / 32 - 16);
+            let d = x * x + y * y;
+            let a = if d < 144 { 255 } else if d < 256 { 128 } else { 0 };
+            [(i % 251) as u8, (i % 173) as u8, (i % 97) as u8, a]
+        })
+        .collect()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut fb = PixelBuffer::new(W, H);
+    let spr = sprite();
+
+    c.bench_function("clear", |b| b.iter(|| fb.clear(black_box([10, 20, 30, 255]))));
+    c.bench_function("line x100", |b| {
+        b.iter(|| {
+            for i in 0..100 { fb.line(0, i, W as i32 - 1, H as i32 - 1 - i, [255, 255, 255, 255]); }
+        })
+    });
+    c.bench_function("fill_rect 200x120", |b| b.iter(|| fb.fill_rect(black_box(50), 40, 200, 120, [200, 80, 40, 255])));
+    c.bench_function("blit_rgba 32x32 x500", |b| {
+        b.iter(|| {
+            for i in 0..500 { fb.blit_rgba((i * 37) % W as i32 - 16, (i * 23) % H as i32 - 16, 32, 32, &spr); }
+        })
+    });
+    c.bench_function("blit_rgba_scaled 32x32 @3x x100", |b| {
+        b.iter(|| {
+            for i in 0..100 { fb.blit_rgba_scaled((i * 37) % W as i32 - 48, (i * 23) % H as i32 - 48, 32, 32, &spr, (3.0, 3.0)); }
+        })
+    });
+    // CPU side of the per-frame upload: diff against the last uploaded frame,
+    // stage the changed bytes and record them as shown (the GPU copy needs a
+    // window). Alternating between two frames keeps every iteration a change.
+    let mut scratch = Scratch::new();
+    let full = Rect::new(0, 0, W, H);
+    fb.clear([90, 60, 30, 255]);
+    let mut one_px = fb.clone();
+    one_px.put(W as i32 / 2, H as i32 / 2, [1, 2, 3, 255]);
+    let mut other = fb.clone();
+    other.clear([0, 0, 0, 255]);
+    other.blit_rgba(0, 0, 32, 32, &spr);
+    for (name, a, b, expect) in [
+        ("upload stage (one pixel changed)", &fb, &one_px, Rect::new(W as i32 / 2, H as i32 / 2, 1, 1)),
+        ("upload stage (full frame changed)", &fb, &other, full),
+    ] {
+        let mut up = UploadState::new();
+        assert_eq!(up.stage(a, &scratch).map(|s| s.0), Some(full));
+        assert_eq!(up.stage(b, &scratch).map(|s| s.0), Some(expect));
+        scratch.reset();
+        let mut flip = false;
+        c.bench_function(name, |bench| {
+            bench.iter(|| {
+                flip = !flip;
+                let len = up.stage(if flip { a } else { b }, &scratch).map(|(_, data)| data.len());
+                black_box(len);
+                scratch.reset();
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+//! -------- Accessibility: color-vision filters --------
+//!
+//! Post-process filters a game can expose in its options menu: simulate a
+//! color-vision deficiency (for checking your own palette), daltonize to make
+//! colors distinguishable for affected players, or outline luminance edges in
+//! a high-contrast color. Set via `PixEngine::set_color_filter`.
+
+use crate::scratch::Scratch;
+use crate::PixelBuffer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorVision {
+    /// Red-blind
+    Protanopia,
+    /// Green-blind (most common)
+    Deuteranopia,
+    /// Blue-blind
+    Tritanopia,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    /// Show what players with this deficiency see
+    Simulate(ColorVision),
+    /// Shift lost contrast into channels the player can see
+    Daltonize(ColorVision),
+    /// Draw `color` on pixels whose luminance differs from a neighbour by more than `threshold`
+    HighContrast { color: [u8; 4], threshold: u8 },
+}
+
+// Machado et al. 2009, severity 1.0, scaled by 1024 for integer math
+const PROTAN: [[i32; 3]; 3] = [[156, 1078, -210], [117, 805, 102], [-4, -49, 1077]];
+const DEUTAN: [[i32; 3]; 3] = [[376, 881, -233], [287, 689, 49], [-12, 44, 992]];
+const TRITAN: [[i32; 3]; 3] = [[1286, -79, -183], [-80, 953, 151], [5, 708, 311]];
+
+impl ColorVision {
+    fn matrix(self) -> &'static [[i32; 3]; 3] {
+        match self {
+            ColorVision::Protanopia => &PROTAN,
+            ColorVision::Deuteranopia => &DEUTAN,
+            ColorVision::Tritanopia => &TRITAN,
+        }
+    }
+    #[inline]
+    fn simulate(self, c: [i32; 3]) -> [i32; 3] {
+        let m = self.matrix();
+        let row = |r: &[i32; 3]| (r[0] * c[0] + r[1] * c[1] + r[2] * c[2]) >> 10;
+        [row(&m[0]), row(&m[1]), row(&m[2])]
+    }
+}
+
+#[inline]
+fn luma(p: &[u8]) -> i32 {
+    (p[0] as i32 * 77 + p[1] as i32 * 150 + p[2] as i32 * 29) >> 8
+}
+
+impl PixelBuffer {
+    /// Apply a color-vision filter to the whole buffer
+    pub fn apply_color_filter(&mut self, filter: ColorFilter) {
+        self.apply_color_filter_in(filter, &Scratch::new());
+    }
+    /// `apply_color_filter` taking its temporaries from `scratch`
+    pub fn apply_color_filter_in(&mut self, filter: ColorFilter, scratch: &Scratch) {
+        match filter {
+            ColorFilter::None => {}
+            ColorFilter::Simulate(cv) => {
+                self.for_each_pixel(|p| {
+                    let s = cv.simulate([p[0] as i32, p[1] as i32, p[2] as i32]);
+                    for i in 0..3 { p[i] = s[i].clamp(0, 255) as u8; }
+                });
+            }
+            ColorFilter::Daltonize(cv) => {
+                self.for_each_pixel(|p| {
+                    let c = [p[0] as i32, p[1] as i32, p[2] as i32];
+                    let s = cv.simulate(c);
+                    let e = [c[0] - s[0], c[1] - s[1], c[2] - s[2]];
+                    // Fidaner et al.: move the error the viewer can't see into G/B
+                    let out = [c[0], c[1] + (e[0] * 7 / 10) + e[1], c[2] + (e[0] * 7 / 10) + e[2]];
+                    for i in 0..3 { p[i] = out[i].clamp(0, 255) as u8; }
+                });
+            }
+            ColorFilter::HighContrast { color, threshold } => {
+                let (w, h) = (self.w as usize, self.h as usize);
+                let raw = self.as_bytes_mut();
+                let lum = scratch.alloc::<i32>(w * h);
+                for (l, p) in lum.iter_mut().zip(raw.chunks_exact(4)) { *l = luma(p); }
+                let t = threshold as i32;
+                for y in 0..h {
+                    for x in 0..w {
+                        let i = y * w + x;
+                        let edge = (x + 1 < w && (lum[i] - lum[i + 1]).abs() > t)
+                            || (y + 1 < h && (lum[i] - lum[i + w]).abs() > t);
+                        if edge { raw[i * 4..i * 4 + 4].copy_from_slice(&color); }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! -------- Atlas: runtime texture atlas packing --------
+//!
+//! Collect many small images, pack them into one `PixelBuffer` (shelf packing,
+//! tallest first) and look regions up by name. Blit straight from the atlas on
+//! the CPU, or upload it once as a single GPU texture.
+
+use crate::{make_nearest_texture, Assets, PixResult, PixelBuffer, Rect};
+use image::RgbaImage;
+use piston_window::G2dTexture;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AtlasError {
+    /// Everything didn't fit within the maximum size
+    TooLarge { max: (u32, u32) },
+    /// Two images were added under the same name
+    DuplicateName(String),
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::TooLarge { max } => write!(f, "images don't fit in a {}x{} atlas", max.0, max.1),
+            AtlasError::DuplicateName(n) => write!(f, "duplicate atlas entry '{n}'"),
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+pub struct AtlasBuilder {
+    max: (u32, u32),
+    padding: u32,
+    images: Vec<(String, RgbaImage)>,
+}
+
+impl AtlasBuilder {
+    /// Atlas may grow up to `max_w`x`max_h` (power-of-two steps from 64)
+    pub fn new(max_w: u32, max_h: u32) -> Self {
+        Self { max: (max_w, max_h), padding: 1, images: Vec::new() }
+    }
+    /// Transparent gap between entries (default 1) to avoid bleeding when sampled
+    pub fn padding(mut self, px: u32) -> Self {
+        self.padding = px;
+        self
+    }
+    pub fn add(&mut self, name: &str, image: RgbaImage) -> &mut Self {
+        self.images.push((name.to_string(), image));
+        self
+    }
+    /// Add a sprite given as rows of RGBA pixels
+    pub fn add_pixels(&mut self, name: &str, w: u32, h: u32, pixels: &[[u8; 4]]) -> &mut Self {
+        let img = RgbaImage::from_fn(w, h, |x, y| image::Rgba(pixels[(y * w + x) as usize]));
+        self.add(name, img)
+    }
+    pub fn build(self) -> Result<Atlas, AtlasError> {
+        let mut seen = HashMap::new();
+        for (i, (name, _)) in self.images.iter().enumerate() {
+            if seen.insert(name.as_str(), i).is_some() {
+                return Err(AtlasError::DuplicateName(name.clone()));
+            }
+        }
+        // tallest first packs shelves tightly
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse((self.images[i].1.height(), self.images[i].1.width())));
+
+        let (mut w, mut h) = (64u32.min(self.max.0), 64u32.min(self.max.1));
+        let placements = loop {
+            if let Some(p) = self.pack(&order, w, h) { break p; }
+            if w >= self.max.0 && h >= self.max.1 {
+                return Err(AtlasError::TooLarge { max: self.max });
+            }
+            // grow the shorter side first to stay roughly square
+            if (w <= h && w < self.max.0) || h >= self.max.1 { w = (w * 2).min(self.max.0); } else { h = (h * 2).min(self.max.1); }
+        };
+
+        let mut buffer = PixelBuffer::new(w, h);
+        buffer.clear([0, 0, 0, 0]);
+        let mut regions = HashMap::new();
+        for (i, r) in placements {
+            let (name, img) = &self.images[i];
+            for (x, y, p) in img.enumerate_pixels() {
+                buffer.set_px(r.x as u32 + x, r.y as u32 + y, p.0);
+            }
+            regions.insert(name.clone(), r);
+        }
+        Ok(Atlas { buffer, regions })
+    }
+    /// Shelf packing into a `w`x`h` area; `None` if it doesn't fit
+    fn pack(&self, order: &[usize], w: u32, h: u32) -> Option<Vec<(usize, Rect)>> {
+        let pad = self.padding;
+        let (mut x, mut y, mut shelf_h) = (0u32, 0u32, 0u32);
+        let mut out = Vec::with_capacity(order.len());
+        for &i in order {
+            let (iw, ih) = self.images[i].1.dimensions();
+            if x + iw > w {
+                x = 0;
+                y += shelf_h + pad;
+                shelf_h = 0;
+            }
+            if x + iw > w || y + ih > h { return None; }
+            out.push((i, Rect::new(x as i32, y as i32, iw, ih)));
+            x += iw + pad;
+            shelf_h = shelf_h.max(ih);
+        }
+        Some(out)
+    }
+}
+
+/// Packed images plus their named regions
+pub struct Atlas {
+    buffer: PixelBuffer,
+    regions: HashMap<String, Rect>,
+}
+
+impl Atlas {
+    #[inline] pub fn buffer(&self) -> &PixelBuffer { &self.buffer }
+    #[inline] pub fn region(&self, name: &str) -> Option<Rect> { self.regions.get(name).copied() }
+    pub fn regions(&self) -> impl Iterator<Item = (&str, Rect)> {
+        self.regions.iter().map(|(n, r)| (n.as_str(), *r))
+    }
+    /// Upload the whole atlas as one nearest-filtered texture
+    pub fn upload(&self, assets: &mut Assets) -> PixResult<G2dTexture> {
+        make_nearest_texture(assets.tex_ctx()?, &self.buffer.to_image())
+    }
+    /// Alpha-blit a named region with its top-left at (x, y); false if unknown
+    pub fn blit(&self, fb: &mut PixelBuffer, name: &str, x: i32, y: i32) -> bool {
+        let Some(r) = self.region(name) else { return false };
+        let dst = Rect::new(x, y, r.w, r.h);
+        let Some(vis) = dst.intersect(&Rect::new(0, 0, fb.w, fb.h)) else { return true };
+        for py in vis.y..vis.bottom() {
+            for px in vis.x..vis.right() {
+                let s = self.buffer.px((r.x + px - x) as u32, (r.y + py - y) as u32);
+                fb.blend(px as u32, py as u32, s);
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,79 @@
+//! -------- Batch: GPU-drawn sprite layers --------
+//!
+//! For scenes with thousands of sprites a layer can skip CPU blitting: give it
+//! a `SpriteBatch` over an uploaded atlas texture (`Atlas::upload`) and record
+//! regions of it each frame. The engine draws every batch as one textured
+//! triangle list on top of the scaled canvas, so GPU layers sit above all CPU
+//! layers (in layer order among themselves), ignore the layer's blend mode and
+//! are not touched by the canvas post steps (LUT, PostFx, filters).
+
+use crate::{profile, Rect};
+use piston_window::triangulation::{rect_tri_list_uv, rect_tri_list_xy};
+use piston_window::math::Matrix2d;
+use piston_window::{DrawState, G2dTexture, Graphics, BACK_END_MAX_VERTEX_COUNT};
+
+/// One textured quad in canvas pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpuSprite {
+    /// Source region in the batch texture
+    pub src: Rect,
+    /// Top-left corner on the canvas (layer space, before scroll)
+    pub pos: (f32, f32),
+    /// Size multiplier; negative mirrors around `pos`
+    pub scale: (f32, f32),
+    /// Multiplied with the texture color
+    pub tint: [u8; 4],
+}
+
+impl GpuSprite {
+    pub fn new(src: Rect, x: f32, y: f32) -> Self {
+        Self { src, pos: (x, y), scale: (1.0, 1.0), tint: [255; 4] }
+    }
+}
+
+/// Sprites drawn from a single texture, in submission order
+#[derive(Clone)]
+pub struct SpriteBatch {
+    texture: G2dTexture,
+    sprites: Vec<GpuSprite>,
+}
+
+impl SpriteBatch {
+    pub fn new(texture: G2dTexture) -> Self { Self { texture, sprites: Vec::new() } }
+    #[inline] pub fn texture(&self) -> &G2dTexture { &self.texture }
+    /// Swap the texture (e.g. after rebuilding the atlas); keeps queued sprites
+    pub fn set_texture(&mut self, texture: G2dTexture) { self.texture = texture; }
+    /// Queue `src` at its natural size with its top-left at (x, y)
+    #[inline] pub fn draw(&mut self, src: Rect, x: f32, y: f32) { self.sprites.push(GpuSprite::new(src, x, y)); }
+    #[inline] pub fn push(&mut self, sprite: GpuSprite) { self.sprites.push(sprite); }
+    #[inline] pub fn sprites(&self) -> &[GpuSprite] { &self.sprites }
+    #[inline] pub fn len(&self) -> usize { self.sprites.len() }
+    #[inline] pub fn is_empty(&self) -> bool { self.sprites.is_empty() }
+    pub fn clear(&mut self) { self.sprites.clear(); }
+
+    /// Emit every sprite as two triangles in one `tri_list_uv_c` call; `transform`
+    /// maps layer pixels to the window and `opacity` scales every tint's alpha
+    pub(crate) fn render<G: Graphics<Texture = G2dTexture>>(&self, ds: &DrawState, transform: Matrix2d, opacity: u8, g: &mut G) {
+        if self.sprites.is_empty() { return; }
+        let _p = profile::scope("sprite_batch");
+        const QUADS: usize = BACK_END_MAX_VERTEX_COUNT / 6;
+        let (mut xy, mut uv, mut rgba) = (Vec::with_capacity(QUADS * 6), Vec::with_capacity(QUADS * 6), Vec::with_capacity(QUADS * 6));
+        g.tri_list_uv_c(ds, &self.texture, |f| {
+            for chunk in self.sprites.chunks(QUADS) {
+                xy.clear();
+                uv.clear();
+                rgba.clear();
+                for s in chunk {
+                    let (w, h) = (s.src.w as f64 * s.scale.0 as f64, s.src.h as f64 * s.scale.1 as f64);
+                    let (x, y) = (s.pos.0 as f64, s.pos.1 as f64);
+                    xy.extend_from_slice(&rect_tri_list_xy(transform, [x, y, w, h]));
+                    uv.extend_from_slice(&rect_tri_list_uv(&self.texture, [s.src.x as f64, s.src.y as f64, s.src.w as f64, s.src.h as f64]));
+                    let a = s.tint[3] as u32 * opacity as u32 / 255;
+                    let c = [s.tint[0] as f32 / 255.0, s.tint[1] as f32 / 255.0, s.tint[2] as f32 / 255.0, a as f32 / 255.0];
+                    rgba.extend_from_slice(&[c; 6]);
+                }
+                f(&xy, &uv, &rgba);
+            }
+        });
+    }
+}
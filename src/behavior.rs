@@ -0,0 +1,115 @@
+//! -------- Behavior: linear scripts for entities --------
+//!
+//! Write sequences the way you'd describe them instead of as hand-rolled state
+//! machines:
+//!
+//! ```ignore
+//! let mut b = Behavior::new()
+//!     .wait(0.5)
+//!     .until(|e: &mut Enemy, dt| e.move_towards(200.0, 40.0, dt)) // true = arrived
+//!     .then(|e| e.fire())
+//!     .looping();
+//! // in Scene::update:
+//! b.update(&mut self.enemy, dt);
+//! ```
+//!
+//! Each behavior is generic over the entity (or whole scene) type it drives and
+//! is advanced on the fixed step; leftover time from a finished `wait`/`during`
+//! flows into the next step so timing doesn't drift.
+
+type Action<T> = Box<dyn FnMut(&mut T)>;
+type Condition<T> = Box<dyn FnMut(&mut T, f64) -> bool>;
+type Tween<T> = Box<dyn FnMut(&mut T, f32)>;
+
+enum Step<T> {
+    Wait(f64),
+    Do(Action<T>),
+    Until(Condition<T>),
+    During(f64, Tween<T>),
+}
+
+pub struct Behavior<T> {
+    steps: Vec<Step<T>>,
+    index: usize,
+    elapsed: f64,
+    looping: bool,
+}
+
+impl<T> Default for Behavior<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T> Behavior<T> {
+    pub fn new() -> Self {
+        Self { steps: Vec::new(), index: 0, elapsed: 0.0, looping: false }
+    }
+    /// Do nothing for `secs` seconds
+    pub fn wait(mut self, secs: f64) -> Self {
+        self.steps.push(Step::Wait(secs.max(0.0)));
+        self
+    }
+    /// Run a one-shot action, then continue immediately
+    pub fn then(mut self, f: impl FnMut(&mut T) + 'static) -> Self {
+        self.steps.push(Step::Do(Box::new(f)));
+        self
+    }
+    /// Call `f(target, dt)` every tick until it returns true (move-to, wait-for-flag, ...)
+    pub fn until(mut self, f: impl FnMut(&mut T, f64) -> bool + 'static) -> Self {
+        self.steps.push(Step::Until(Box::new(f)));
+        self
+    }
+    /// Call `f(target, t)` every tick for `secs` with t rising 0.0..=1.0 (tweens, fades)
+    pub fn during(mut self, secs: f64, f: impl FnMut(&mut T, f32) + 'static) -> Self {
+        self.steps.push(Step::During(secs.max(0.0), Box::new(f)));
+        self
+    }
+    /// Restart from the first step after the last one finishes
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+    #[inline] pub fn is_finished(&self) -> bool { self.index >= self.steps.len() }
+    /// Start over from the first step
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.elapsed = 0.0;
+    }
+
+    /// Advance by `dt`, running as many steps as complete within it
+    pub fn update(&mut self, target: &mut T, dt: f64) {
+        let mut dt = dt;
+        // bound the work per call so a looping script of instant steps can't spin forever
+        let mut budget = self.steps.len() + 1;
+        while budget > 0 {
+            if self.index >= self.steps.len() {
+                if !self.looping || self.steps.is_empty() { return; }
+                self.index = 0;
+            }
+            budget -= 1;
+            match &mut self.steps[self.index] {
+                Step::Wait(secs) => {
+                    self.elapsed += dt;
+                    if self.elapsed < *secs { return; }
+                    dt = self.elapsed - *secs;
+                }
+                Step::Do(f) => f(target),
+                Step::Until(f) => {
+                    if !f(target, dt) { return; }
+                    // the tick's time was spent on this step
+                    dt = 0.0;
+                }
+                Step::During(secs, f) => {
+                    self.elapsed += dt;
+                    if self.elapsed < *secs {
+                        f(target, (self.elapsed / *secs) as f32);
+                        return;
+                    }
+                    f(target, 1.0);
+                    dt = self.elapsed - *secs;
+                }
+            }
+            self.index += 1;
+            self.elapsed = 0.0;
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! -------- Camera: world <-> canvas mapping --------
+
+use crate::{PixelBuffer, RotoZoom};
+
+/// 2D camera: `(x, y)` is the world position at the canvas's top-left corner
+/// (before rotation), `zoom` is canvas pixels per world unit and `angle` turns
+/// the view about its centre.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+    /// Radians, clockwise; the world appears to turn the other way. Set it to
+    /// the player's heading (minus a quarter turn) to keep them facing up.
+    pub angle: f32,
+    /// Canvas size the camera renders into
+    pub view_w: u32,
+    pub view_h: u32,
+}
+
+impl Camera {
+    pub fn new(view_w: u32, view_h: u32) -> Self {
+        Self { x: 0.0, y: 0.0, zoom: 1.0, angle: 0.0, view_w, view_h }
+    }
+    #[inline]
+    pub fn world_to_screen(&self, wx: f32, wy: f32) -> (f32, f32) {
+        if self.angle == 0.0 { return ((wx - self.x) * self.zoom, (wy - self.y) * self.zoom); }
+        let (cx, cy) = self.center();
+        let (hw, hh) = (self.view_w as f32 * 0.5, self.view_h as f32 * 0.5);
+        let (sin, cos) = self.angle.sin_cos();
+        let (dx, dy) = (wx - cx, wy - cy);
+        ((dx * cos + dy * sin) * self.zoom + hw, (dy * cos - dx * sin) * self.zoom + hh)
+    }
+    #[inline]
+    pub fn screen_to_world(&self, sx: f32, sy: f32) -> (f32, f32) {
+        if self.angle == 0.0 { return (sx / self.zoom + self.x, sy / self.zoom + self.y); }
+        let (cx, cy) = self.center();
+        let (hw, hh) = (self.view_w as f32 * 0.5, self.view_h as f32 * 0.5);
+        let (sin, cos) = self.angle.sin_cos();
+        let (dx, dy) = ((sx - hw) / self.zoom, (sy - hh) / self.zoom);
+        (dx * cos - dy * sin + cx, dx * sin + dy * cos + cy)
+    }
+    /// Copy with the position snapped down to whole canvas pixels
+    pub fn snapped(&self) -> Camera {
+        let snap = |v: f32| (v * self.zoom).floor() / self.zoom;
+        Camera { x: snap(self.x), y: snap(self.y), ..*self }
+    }
+    /// What `snapped` dropped, in canvas pixels (0.0..1.0 per axis)
+    pub fn subpixel(&self) -> (f32, f32) {
+        let frac = |v: f32| { let p = v * self.zoom; p - p.floor() };
+        (frac(self.x), frac(self.y))
+    }
+    /// World point at the middle of the view (the rotation pivot)
+    #[inline]
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.view_w as f32 * 0.5 / self.zoom, self.y + self.view_h as f32 * 0.5 / self.zoom)
+    }
+    /// Put the given world point in the middle of the view
+    pub fn center_on(&mut self, wx: f32, wy: f32) {
+        self.x = wx - self.view_w as f32 * 0.5 / self.zoom;
+        self.y = wy - self.view_h as f32 * 0.5 / self.zoom;
+    }
+    /// Multiply the zoom by `factor`, keeping the world point under canvas
+    /// position (screen_x, screen_y) in place (mouse-wheel zoom)
+    pub fn zoom_at(&mut self, screen_x: f32, screen_y: f32, factor: f32) {
+        if factor <= 0.0 || !factor.is_finite() { return; }
+        let (wx, wy) = self.screen_to_world(screen_x, screen_y);
+        self.zoom *= factor;
+        let (nx, ny) = self.screen_to_world(screen_x, screen_y);
+        self.x += wx - nx;
+        self.y += wy - ny;
+    }
+    /// World-space area covered by the view: (x, y, w, h); the bounding box of
+    /// the turned view when rotated
+    pub fn visible_rect(&self) -> (f32, f32, f32, f32) {
+        let (w, h) = (self.view_w as f32 / self.zoom, self.view_h as f32 / self.zoom);
+        if self.angle == 0.0 { return (self.x, self.y, w, h); }
+        let (sin, cos) = self.angle.sin_cos();
+        let (bw, bh) = (w * cos.abs() + h * sin.abs(), w * sin.abs() + h * cos.abs());
+        let (cx, cy) = self.center();
+        (cx - bw * 0.5, cy - bh * 0.5, bw, bh)
+    }
+    /// Inclusive tile index range (x0, y0, x1, y1) touching the view
+    pub fn visible_tiles(&self, tile_w: u32, tile_h: u32) -> (i32, i32, i32, i32) {
+        let r = self.visible_tile_rect(tile_w, tile_h);
+        (r.x0, r.y0, r.x1, r.y1)
+    }
+    /// Tiles of `tile_w`x`tile_h` world units touching the view, at any zoom,
+    /// rotation or sign of position (`iter` walks the indices)
+    pub fn visible_tile_rect(&self, tile_w: u32, tile_h: u32) -> TileRect {
+        let (x, y, w, h) = self.visible_rect();
+        let (tw, th) = (tile_w.max(1) as f64, tile_h.max(1) as f64);
+        // floor, not truncation, so negative positions map to the right tile;
+        // the far edge is exclusive, so a view ending on a tile seam stops before it
+        let (x, y, w, h) = (x as f64, y as f64, w as f64, h as f64);
+        let x0 = (x / tw).floor() as i32;
+        let y0 = (y / th).floor() as i32;
+        let x1 = ((x + w) / tw).ceil() as i32 - 1;
+        let y1 = ((y + h) / th).ceil() as i32 - 1;
+        TileRect { x0, y0, x1: x1.max(x0), y1: y1.max(y0) }
+    }
+    /// Is a world-space rectangle at least partly on screen?
+    pub fn is_visible(&self, wx: f32, wy: f32, w: f32, h: f32) -> bool {
+        let (vx, vy, vw, vh) = self.visible_rect();
+        wx < vx + vw && wy < vy + vh && wx + w > vx && wy + h > vy
+    }
+}
+
+/// Inclusive range of tile indices
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TileRect {
+    pub x0: i32,
+    pub y0: i32,
+    pub x1: i32,
+    pub y1: i32,
+}
+
+impl TileRect {
+    #[inline] pub fn width(&self) -> u32 { (self.x1 - self.x0 + 1).max(0) as u32 }
+    #[inline] pub fn height(&self) -> u32 { (self.y1 - self.y0 + 1).max(0) as u32 }
+    #[inline]
+    pub fn contains(&self, tx: i32, ty: i32) -> bool {
+        tx >= self.x0 && tx <= self.x1 && ty >= self.y0 && ty <= self.y1
+    }
+    /// Tile indices (tx, ty), left to right then top to bottom
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (x0, x1) = (self.x0, self.x1);
+        (self.y0..=self.y1).flat_map(move |ty| (x0..=x1).map(move |tx| (tx, ty)))
+    }
+}
+
+/// Background drawn through the camera but scrolled by only a fraction of its
+/// movement: 0.0 stays fixed on screen, 1.0 moves with the world, in between
+/// reads as distance. Rotation is ignored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParallaxLayer {
+    pub factor_x: f32,
+    pub factor_y: f32,
+    /// World position of the image's top-left at camera (0, 0)
+    pub origin: (f32, f32),
+    /// Repeat the image across the view horizontally / vertically
+    pub wrap_x: bool,
+    pub wrap_y: bool,
+}
+
+impl ParallaxLayer {
+    pub fn new(factor_x: f32, factor_y: f32) -> Self {
+        Self { factor_x, factor_y, origin: (0.0, 0.0), wrap_x: false, wrap_y: false }
+    }
+    /// Tiled in both directions (starfields, clouds)
+    pub fn tiled(factor_x: f32, factor_y: f32) -> Self {
+        Self { wrap_x: true, wrap_y: true, ..Self::new(factor_x, factor_y) }
+    }
+    /// Canvas position of the image's top-left for this camera
+    pub fn screen_pos(&self, cam: &Camera) -> (f32, f32) {
+        ((self.origin.0 - cam.x * self.factor_x) * cam.zoom, (self.origin.1 - cam.y * self.factor_y) * cam.zoom)
+    }
+}
+
+/// Snapped start positions of the copies covering `0..view` (one when not wrapping)
+fn parallax_span(pos: f32, size: f32, view: u32, wrap: bool) -> impl Iterator<Item = (i32, i32)> {
+    let start = if wrap { pos - (pos / size).ceil() * size } else { pos };
+    let count = if wrap { ((view as f32 - start) / size).ceil().max(0.0) as i32 } else { 1 };
+    (0..count).map(move |i| {
+        let a = start + i as f32 * size;
+        (a.floor() as i32, (a + size).floor() as i32)
+    })
+}
+
+impl PixelBuffer {
+    /// Draw a background image through `cam` per `layer` (scaled by the zoom,
+    /// tiled without seams when wrapping)
+    pub fn draw_parallax(&mut self, cam: &Camera, layer: &ParallaxLayer, w: u32, h: u32, pixels: &[[u8; 4]]) {
+        if w == 0 || h == 0 || cam.zoom <= 0.0 { return; }
+        let (px, py) = layer.screen_pos(cam);
+        let (tw, th) = (w as f32 * cam.zoom, h as f32 * cam.zoom);
+        for (y0, y1) in parallax_span(py, th, self.h, layer.wrap_y) {
+            for (x0, x1) in parallax_span(px, tw, self.w, layer.wrap_x) {
+                if cam.zoom == 1.0 {
+                    self.blit_rgba(x0, y0, w, h, pixels);
+                } else {
+                    let scale = ((x1 - x0) as f32 / w as f32, (y1 - y0) as f32 / h as f32);
+                    self.blit_rgba_scaled(x0, y0, w, h, pixels, scale);
+                }
+            }
+        }
+    }
+
+    /// Blit a sprite placed in world space (one sprite pixel per world unit),
+    /// culled against the camera and scaled by its zoom. The on-screen size is
+    /// taken from the snapped corner positions, so neighbouring tiles never gap.
+    /// A rotated camera draws through the rotozoom blitter instead.
+    pub fn blit_to_camera(
+        &mut self,
+        cam: &Camera,
+        wx: f32,
+        wy: f32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+    ) {
+        if !cam.is_visible(wx, wy, sprite_w as f32, sprite_h as f32) { return; }
+        let (x0, y0) = cam.world_to_screen(wx, wy);
+        if cam.angle != 0.0 {
+            let rz = RotoZoom { angle: -cam.angle, scale: cam.zoom, ..RotoZoom::default() };
+            self.blit_rgba_rotozoom(x0, y0, sprite_w, sprite_h, pixels, rz);
+            return;
+        }
+        if cam.zoom == 1.0 {
+            self.blit_rgba(x0.floor() as i32, y0.floor() as i32, sprite_w, sprite_h, pixels);
+            return;
+        }
+        let (x1, y1) = cam.world_to_screen(wx + sprite_w as f32, wy + sprite_h as f32);
+        let (x0, y0, x1, y1) = (x0.floor(), y0.floor(), x1.floor(), y1.floor());
+        let scale = ((x1 - x0) / sprite_w.max(1) as f32, (y1 - y0) / sprite_h.max(1) as f32);
+        self.blit_rgba_scaled(x0 as i32, y0 as i32, sprite_w, sprite_h, pixels, scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlendMode;
+
+    const ONE: [u8; 4] = [1, 0, 0, 255];
+
+    /// Black buffer that counts writes in the red channel
+    fn counter(w: u32, h: u32) -> PixelBuffer {
+        let mut fb = PixelBuffer::new(w, h);
+        fb.set_blend_mode(BlendMode::Add);
+        fb
+    }
+
+    fn coverage(fb: &PixelBuffer) -> Vec<u8> {
+        fb.rows().flatten().map(|p| p[0]).collect()
+    }
+
+    #[test]
+    fn scaled_blit_covers_rounded_size_once() {
+        for (scale, size) in [((2.5, 1.5), (8, 3)), ((0.4, 0.75), (1, 2)), ((1.0 / 3.0, 2.0), (1, 4))] {
+            let mut fb = counter(16, 16);
+            fb.blit_rgba_scaled(2, 3, 3, 2, &[ONE; 6], scale);
+            for (i, n) in coverage(&fb).into_iter().enumerate() {
+                let (x, y) = (i as u32 % 16, i as u32 / 16);
+                let inside = (2..2 + size.0).contains(&x) && (3..3 + size.1).contains(&y);
+                assert_eq!(n, inside as u8, "scale {scale:?} at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn scaled_blit_spreads_columns_evenly() {
+        let pixels = [[10, 0, 0, 255], [20, 0, 0, 255], [30, 0, 0, 255]];
+        let mut fb = PixelBuffer::new(8, 1);
+        fb.blit_rgba_scaled(0, 0, 3, 1, &pixels, (2.5, 1.0));
+        let row: Vec<u8> = coverage(&fb);
+        assert!(row.windows(2).all(|w| w[0] <= w[1]), "{row:?}");
+        for c in [10, 20, 30] {
+            let run = row.iter().filter(|&&v| v == c).count();
+            assert!((2..=3).contains(&run), "{row:?}");
+        }
+    }
+
+    #[test]
+    fn scaled_blit_clips_negative_positions() {
+        let pixels: Vec<[u8; 4]> = (0..20).map(|i| [i * 10, 0, 0, 255]).collect();
+        let mut full = PixelBuffer::new(40, 40);
+        full.blit_rgba_scaled(10, 10, 5, 4, &pixels, (1.7, 2.3));
+        let mut clipped = PixelBuffer::new(40, 40);
+        clipped.blit_rgba_scaled(-3, -5, 5, 4, &pixels, (1.7, 2.3));
+        for y in 0..20 {
+            for x in 0..20 {
+                assert_eq!(clipped.px(x, y), full.px(x + 13, y + 15), "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn camera_tiles_meet_without_gaps_or_overlap() {
+        const T: u32 = 8;
+        let (w, h) = (64, 48);
+        for zoom in [0.6, 0.75, 1.0, 1.3, 1.5, 2.0, 2.7, 3.25] {
+            for (x, y) in [(0.0, 0.0), (-13.4, -7.7), (5.3, -2.1), (-0.5, 11.9), (-100.25, -64.6)] {
+                let cam = Camera { x, y, zoom, ..Camera::new(w, h) };
+                let mut fb = counter(w, h);
+                for (tx, ty) in cam.visible_tile_rect(T, T).iter() {
+                    fb.blit_to_camera(&cam, (tx * T as i32) as f32, (ty * T as i32) as f32, T, T, &[ONE; (T * T) as usize]);
+                }
+                let cov = coverage(&fb);
+                assert!(cov.iter().all(|&n| n == 1), "zoom {zoom} at ({x}, {y}): {:?}",
+                    cov.iter().position(|&n| n != 1).map(|i| (i % w as usize, i / w as usize, cov[i])));
+            }
+        }
+    }
+
+    /// Screen overlap of tile (tx, ty) with the view, in pixels (x, y; <= 0 = none)
+    fn tile_overlap(cam: &Camera, t: u32, tx: i32, ty: i32) -> (f32, f32) {
+        let (x0, y0) = cam.world_to_screen((tx * t as i32) as f32, (ty * t as i32) as f32);
+        let (x1, y1) = cam.world_to_screen(((tx + 1) * t as i32) as f32, ((ty + 1) * t as i32) as f32);
+        (x1.min(cam.view_w as f32) - x0.max(0.0), y1.min(cam.view_h as f32) - y0.max(0.0))
+    }
+
+    #[test]
+    fn visible_tile_rect_matches_world_to_screen() {
+        const EPS: f32 = 1e-3;
+        let mut seed = 0x2545_f491_u32;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1u32 << 24) as f32
+        };
+        for _ in 0..2000 {
+            let zoom = 0.25 + rand() * 4.0;
+            let (x, y) = ((rand() - 0.6) * 3000.0, (rand() - 0.6) * 3000.0);
+            let t = [1, 7, 8, 16, 32][(rand() * 5.0) as usize % 5];
+            let cam = Camera { x, y, zoom, ..Camera::new(64 + (rand() * 200.0) as u32, 36 + (rand() * 120.0) as u32) };
+            let r = cam.visible_tile_rect(t, t);
+            let ctx = format!("zoom {zoom} at ({x}, {y}), tile {t}: {r:?}");
+            // every tile on screen is in the rect
+            for ty in r.y0 - 3..=r.y1 + 3 {
+                for tx in r.x0 - 3..=r.x1 + 3 {
+                    let (ox, oy) = tile_overlap(&cam, t, tx, ty);
+                    if ox > EPS && oy > EPS { assert!(r.contains(tx, ty), "({tx}, {ty}) missing, {ctx}"); }
+                }
+            }
+            // and the border tiles are on screen
+            for (tx, ty) in [(r.x0, r.y0), (r.x1, r.y1)] {
+                let (ox, oy) = tile_overlap(&cam, t, tx, ty);
+                assert!(ox > -EPS && oy > -EPS, "({tx}, {ty}) off screen, {ctx}");
+            }
+        }
+    }
+
+    #[test]
+    fn tile_rect_iterates_rows_in_order() {
+        let r = TileRect { x0: -2, y0: -1, x1: 1, y1: 1 };
+        let tiles: Vec<_> = r.iter().collect();
+        assert_eq!(tiles.len() as u32, r.width() * r.height());
+        assert_eq!(tiles.len(), 12);
+        assert_eq!(&tiles[..5], &[(-2, -1), (-1, -1), (0, -1), (1, -1), (-2, 0)]);
+        assert_eq!(tiles.last(), Some(&(1, 1)));
+        assert!(tiles.iter().all(|&(x, y)| r.contains(x, y)));
+        let single = TileRect { x0: 3, y0: 4, x1: 3, y1: 4 };
+        assert_eq!(single.iter().collect::<Vec<_>>(), [(3, 4)]);
+    }
+
+    #[test]
+    fn camera_blit_lands_on_snapped_corner() {
+        let cam = Camera { x: -3.6, y: 2.2, zoom: 1.5, ..Camera::new(32, 32) };
+        let mut fb = counter(32, 32);
+        fb.blit_to_camera(&cam, 1.0, 4.0, 4, 4, &[ONE; 16]);
+        // corners at screen (6.9, 2.7) and (12.9, 8.7) snap to 6..12 x 2..8
+        let cov = coverage(&fb);
+        for (i, n) in cov.into_iter().enumerate() {
+            let (x, y) = (i as u32 % 32, i as u32 / 32);
+            assert_eq!(n, ((6..12).contains(&x) && (2..8).contains(&y)) as u8, "({x}, {y})");
+        }
+    }
+}
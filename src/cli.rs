@@ -0,0 +1,69 @@
+//! -------- CLI: opt-in engine overrides from the command line --------
+//!
+//! `--windowed`, `--fullscreen`, `--scale N`, `--vsync`, `--no-vsync` and
+//! `--record FILE` (also `--opt=value`). Anything else is left in `rest` for the
+//! game. Nothing is parsed unless the game calls `EngineArgs::from_env`.
+
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EngineArgs {
+    /// `Some(true)` = --fullscreen, `Some(false)` = --windowed
+    pub fullscreen: Option<bool>,
+    /// Window size as a multiple of the canvas size
+    pub scale: Option<u32>,
+    pub vsync: Option<bool>,
+    /// Where the game should record input (the engine only passes it through)
+    pub record: Option<PathBuf>,
+    /// Arguments the engine didn't recognise, in order
+    pub rest: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArgsError(pub String);
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+
+impl std::error::Error for ArgsError {}
+
+impl EngineArgs {
+    /// Parse `std::env::args()` (skipping the program name)
+    pub fn from_env() -> Result<Self, ArgsError> {
+        Self::parse(std::env::args().skip(1))
+    }
+    pub fn parse<I, S>(args: I) -> Result<Self, ArgsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut out = EngineArgs::default();
+        let mut it = args.into_iter().map(Into::into);
+        while let Some(arg) = it.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| -> Result<String, ArgsError> {
+                inline.clone().or_else(|| it.next()).ok_or_else(|| ArgsError(format!("{name} needs a value")))
+            };
+            match flag.as_str() {
+                "--windowed" => out.fullscreen = Some(false),
+                "--fullscreen" => out.fullscreen = Some(true),
+                "--vsync" => out.vsync = Some(true),
+                "--no-vsync" => out.vsync = Some(false),
+                "--scale" => {
+                    let v = value("--scale")?;
+                    let n: u32 = v.parse().map_err(|_| ArgsError(format!("--scale: '{v}' is not a number")))?;
+                    if n == 0 { return Err(ArgsError("--scale must be at least 1".into())); }
+                    out.scale = Some(n);
+                }
+                "--record" => out.record = Some(PathBuf::from(value("--record")?)),
+                _ => out.rest.push(arg),
+            }
+        }
+        Ok(out)
+    }
+}
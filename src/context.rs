@@ -0,0 +1,256 @@
+//! -------- Context: per-game state handed to scenes --------
+//!
+//! Owned by the engine and passed to `Scene::update` / `Scene::draw`: timing,
+//! frame/tick counters, keyboard state, the camera and loaded images, so scenes
+//! don't duplicate engine bookkeeping.
+
+use crate::camera::Camera;
+use crate::gamepad::Gamepads;
+use crate::palette::Palette;
+use crate::players::{Device, PlayerSlots};
+use crate::scratch::Scratch;
+use crate::stats::Stats;
+use crate::{PixResult, PixelBuffer};
+use piston_window::Key;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Keyboard state with per-tick edges
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    held: HashSet<Key>,
+    // edges collected between ticks, exposed during the next tick
+    pending_down: HashSet<Key>,
+    pending_up: HashSet<Key>,
+    down: HashSet<Key>,
+    up: HashSet<Key>,
+}
+
+impl Input {
+    #[inline] pub fn is_down(&self, k: Key) -> bool { self.held.contains(&k) }
+    /// Pressed since the previous fixed update
+    #[inline] pub fn just_pressed(&self, k: Key) -> bool { self.down.contains(&k) }
+    /// Released since the previous fixed update
+    #[inline] pub fn just_released(&self, k: Key) -> bool { self.up.contains(&k) }
+    pub fn held_keys(&self) -> impl Iterator<Item = Key> + '_ { self.held.iter().copied() }
+
+    /// Record a key-down; false for key-repeat of an already held key
+    pub(crate) fn press(&mut self, k: Key) -> bool {
+        if !self.held.insert(k) { return false; }
+        self.pending_down.insert(k);
+        true
+    }
+    /// Record a key-up; false if the key wasn't held
+    pub(crate) fn release(&mut self, k: Key) -> bool {
+        if !self.held.remove(&k) { return false; }
+        self.pending_up.insert(k);
+        true
+    }
+    /// Forget everything (focus loss)
+    pub(crate) fn clear(&mut self) {
+        for k in self.held.drain() { self.pending_up.insert(k); }
+        self.pending_down.clear();
+    }
+    fn begin_tick(&mut self) {
+        std::mem::swap(&mut self.down, &mut self.pending_down);
+        std::mem::swap(&mut self.up, &mut self.pending_up);
+    }
+    fn end_tick(&mut self) {
+        self.down.clear();
+        self.up.clear();
+    }
+}
+
+/// Decoded image ready for `PixelBuffer::blit_rgba`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sprite {
+    pub w: u32,
+    pub h: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Sprite {
+    pub fn from_image(img: &image::RgbaImage) -> Self {
+        Self { w: img.width(), h: img.height(), pixels: img.pixels().map(|p| p.0).collect() }
+    }
+}
+
+/// Window/engine action requested by a scene via `Context::send`; the engine
+/// applies queued commands after each fixed update
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EngineCommand {
+    /// Close the window and return from `PixEngine::run`
+    Quit,
+    SetTitle(String),
+    /// Fullscreen in the engine's `FullscreenMode`, or back to windowed
+    SetFullscreen(bool),
+    ShowCursor(bool),
+    /// Switch the logical resolution (see `PixEngine::set_canvas_size`)
+    SetCanvasSize(u32, u32),
+    /// Frame rate cap (see `PixEngine::set_max_fps`)
+    SetMaxFps(u64),
+}
+
+/// Handle to an image loaded through `Context::load_sprite`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpriteId(u32);
+
+pub struct Context {
+    dt: f64,
+    real_dt: f64,
+    time: f64,
+    real_time: f64,
+    tick: u64,
+    frame: u64,
+    alpha: f64,
+    time_scale: f64,
+    canvas: (u32, u32),
+    pub input: Input,
+    pub camera: Camera,
+    /// Cycling palette: advanced every tick, applied to the frame when composing
+    pub palette: Option<Palette>,
+    /// Latest stick axes of every controller, fed by the engine
+    pub gamepads: Gamepads,
+    /// Local multiplayer slots; once set the engine feeds them keys and gamepad
+    /// events and latches them after every fixed update
+    pub players: Option<PlayerSlots>,
+    sprites: Vec<Sprite>,
+    sprite_paths: HashMap<String, SpriteId>,
+    commands: Vec<EngineCommand>,
+    stats: Stats,
+    scratch: Scratch,
+    world: Option<PixelBuffer>,
+    world_dirty: bool,
+}
+
+impl Context {
+    pub fn new(canvas_w: u32, canvas_h: u32) -> Self {
+        Self {
+            dt: 0.0,
+            real_dt: 0.0,
+            time: 0.0,
+            real_time: 0.0,
+            tick: 0,
+            frame: 0,
+            alpha: 0.0,
+            time_scale: 1.0,
+            canvas: (canvas_w, canvas_h),
+            input: Input::default(),
+            camera: Camera::new(canvas_w, canvas_h),
+            palette: None,
+            gamepads: Gamepads::new(),
+            players: None,
+            sprites: Vec::new(),
+            sprite_paths: HashMap::new(),
+            commands: Vec::new(),
+            stats: Stats::new(),
+            scratch: Scratch::new(),
+            world: None,
+            world_dirty: false,
+        }
+    }
+    /// Fixed step of the current update
+    #[inline] pub fn dt(&self) -> f64 { self.dt }
+    /// Wall-clock time of the current frame, unaffected by time scale
+    #[inline] pub fn real_dt(&self) -> f64 { self.real_dt }
+    /// Simulated seconds elapsed (sum of fixed steps)
+    #[inline] pub fn time(&self) -> f64 { self.time }
+    /// Wall-clock seconds since the engine started
+    #[inline] pub fn real_time(&self) -> f64 { self.real_time }
+    /// Fixed updates run so far
+    #[inline] pub fn tick(&self) -> u64 { self.tick }
+    /// Frames drawn so far
+    #[inline] pub fn frame(&self) -> u64 { self.frame }
+    #[inline] pub fn canvas_size(&self) -> (u32, u32) { self.canvas }
+    /// Achieved FPS/UPS and frame times
+    #[inline] pub fn stats(&self) -> &Stats { &self.stats }
+    /// Temporary buffers for this frame (emptied when the next frame begins)
+    #[inline] pub fn scratch(&self) -> &Scratch { &self.scratch }
+    /// How far the frame being drawn lies between the last fixed update and the
+    /// next, 0.0..1.0 (accumulator / step). Draw at `prev + (cur - prev) * alpha`
+    /// for smooth motion when the frame rate and update rate differ.
+    #[inline] pub fn alpha(&self) -> f64 { self.alpha }
+    /// Global simulation speed (0.0 = frozen, 0.5 = slow motion), read by the
+    /// engine's accumulator from the next frame on and multiplied with
+    /// `Scene::time_scale` and the debug speed. Same as `PixEngine::set_time_scale`.
+    pub fn set_time_scale(&mut self, scale: f64) { self.time_scale = scale.max(0.0); }
+    #[inline] pub fn time_scale(&self) -> f64 { self.time_scale }
+
+    /// Load (or fetch the cached) image at `path`
+    pub fn load_sprite(&mut self, path: &str) -> PixResult<SpriteId> {
+        if let Some(&id) = self.sprite_paths.get(path) { return Ok(id); }
+        let sprite = Sprite::from_image(&image::open(path)?.to_rgba8());
+        let id = self.add_sprite(sprite);
+        self.sprite_paths.insert(path.to_string(), id);
+        Ok(id)
+    }
+    /// Register an image created at runtime
+    pub fn add_sprite(&mut self, sprite: Sprite) -> SpriteId {
+        self.sprites.push(sprite);
+        SpriteId(self.sprites.len() as u32 - 1)
+    }
+    #[inline] pub fn sprite(&self, id: SpriteId) -> &Sprite { &self.sprites[id.0 as usize] }
+
+    /// Oversized world image (one pixel per world unit) drawn behind the canvas.
+    /// Each frame only the part the camera sees is uploaded to the GPU, so
+    /// the canvas shows it wherever it is transparent. Not drawn when headless.
+    pub fn set_world(&mut self, world: Option<PixelBuffer>) {
+        self.world = world;
+        self.world_dirty = true;
+    }
+    #[inline] pub fn world(&self) -> Option<&PixelBuffer> { self.world.as_ref() }
+    /// Draw into the world; marks it for re-upload
+    pub fn world_mut(&mut self) -> Option<&mut PixelBuffer> {
+        self.world_dirty = true;
+        self.world.as_mut()
+    }
+    /// Whether the world changed since the last call
+    pub(crate) fn take_world_dirty(&mut self) -> bool { std::mem::take(&mut self.world_dirty) }
+
+    /// Vibrate `player`'s gamepad (see `players`) at `strength` 0.0..=1.0 for
+    /// `duration`. Returns whether it took effect: keyboard players, empty slots
+    /// and pads that can't vibrate are silently ignored.
+    pub fn rumble(&mut self, player: usize, strength: f32, duration: Duration) -> bool {
+        match self.players.as_ref().and_then(|p| p.device(player)) {
+            Some(Device::Gamepad(id)) => self.gamepads.rumble(id, strength, duration),
+            _ => false,
+        }
+    }
+
+    /// Queue a window/engine action (quit, title, fullscreen, cursor)
+    pub fn send(&mut self, cmd: EngineCommand) { self.commands.push(cmd); }
+    #[inline] pub fn quit(&mut self) { self.send(EngineCommand::Quit); }
+    /// Switch the logical resolution after this update (`EngineCommand::SetCanvasSize`)
+    #[inline] pub fn set_canvas_size(&mut self, w: u32, h: u32) { self.send(EngineCommand::SetCanvasSize(w, h)); }
+
+    pub(crate) fn take_commands(&mut self) -> Vec<EngineCommand> { std::mem::take(&mut self.commands) }
+    /// Bookkeeping after the engine (or a viewport) changed the canvas size
+    pub(crate) fn sync_canvas_size(&mut self, w: u32, h: u32) {
+        self.canvas = (w, h);
+        self.camera.view_w = w;
+        self.camera.view_h = h;
+    }
+    pub(crate) fn set_alpha(&mut self, alpha: f64) { self.alpha = alpha.clamp(0.0, 1.0); }
+    pub(crate) fn begin_frame(&mut self, real_dt: f64) {
+        self.real_dt = real_dt;
+        self.real_time += real_dt;
+        self.scratch.reset();
+    }
+    pub(crate) fn end_frame(&mut self) {
+        self.frame += 1;
+        self.stats.record_frame();
+    }
+    pub(crate) fn record_skipped_frame(&mut self) { self.stats.record_skip(); }
+    pub(crate) fn begin_tick(&mut self, dt: f64) {
+        self.dt = dt;
+        self.input.begin_tick();
+    }
+    pub(crate) fn end_tick(&mut self) {
+        self.time += self.dt;
+        self.tick += 1;
+        if let Some(p) = &mut self.palette { p.update(self.dt); }
+        if let Some(p) = &mut self.players { p.end_tick(); }
+        self.stats.record_update();
+        self.input.end_tick();
+    }
+}
@@ -0,0 +1,117 @@
+//! -------- Crash: panic hook that dumps engine state --------
+//!
+//! Once installed, a panic writes `<dir>/crash-<unix time>/` containing
+//! `log.txt` (panic message plus the most recent `crash::log` lines) and
+//! `input.txt` (recent key events) before the previous panic hook runs. The
+//! engine adds `frame.png` (last presented frame) as it unwinds, so frames are
+//! only copied when a crash actually happens; with `panic = "abort"` there is
+//! no unwinding and no frame. Attach the folder to bug reports.
+
+use crate::PixelBuffer;
+use piston_window::Key;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAX_LOG_LINES: usize = 200;
+const MAX_INPUT_EVENTS: usize = 600;
+
+struct CrashState {
+    dir: PathBuf,
+    /// Folder of the dump written for the current panic
+    last_dump: Option<PathBuf>,
+    frame_no: u64,
+    log: VecDeque<String>,
+    input: VecDeque<(u64, Key, bool)>,
+}
+
+static STATE: Mutex<Option<CrashState>> = Mutex::new(None);
+
+fn with_state(f: impl FnOnce(&mut CrashState)) {
+    // try_lock: never block (or deadlock) the game loop or a panicking thread
+    let mut guard = match STATE.try_lock() {
+        Ok(g) => g,
+        Err(TryLockError::Poisoned(p)) => p.into_inner(),
+        Err(TryLockError::WouldBlock) => return,
+    };
+    if let Some(s) = guard.as_mut() { f(s); }
+}
+
+/// Install the panic hook; dumps go below `dir`. Calling again just changes `dir`.
+pub fn install(dir: impl AsRef<Path>) {
+    let dir = dir.as_ref().to_path_buf();
+    let mut guard = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(s) = guard.as_mut() {
+        s.dir = dir;
+        return;
+    }
+    *guard = Some(CrashState { dir, last_dump: None, frame_no: 0, log: VecDeque::new(), input: VecDeque::new() });
+    drop(guard);
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let mut msg = info.to_string();
+        let _ = write!(msg, "\n\nbacktrace:\n{}", std::backtrace::Backtrace::force_capture());
+        match dump(&msg) {
+            Some(path) => log(format!("crash dump written to {}", path.display())),
+            None => log("crash dump could not be written"),
+        }
+        previous(info);
+    }));
+}
+
+pub fn is_installed() -> bool {
+    STATE.try_lock().map(|g| g.is_some()).unwrap_or(true)
+}
+
+/// Remember a line for the crash log (keeps the last 200)
+pub fn log(line: impl Into<String>) {
+    let line = line.into();
+    with_state(|s| {
+        if s.log.len() == MAX_LOG_LINES { s.log.pop_front(); }
+        s.log.push_back(format!("[frame {}] {line}", s.frame_no));
+    });
+}
+
+/// Count a presented frame, for the frame numbers in the log (engine calls this)
+pub(crate) fn record_frame() {
+    with_state(|s| s.frame_no += 1);
+}
+
+/// Add `frame.png` to the dump of the current panic (the engine calls this
+/// while unwinding)
+pub(crate) fn write_frame(fb: &PixelBuffer) {
+    with_state(|s| {
+        if let Some(dir) = s.last_dump.take() { let _ = fb.to_image().save(dir.join("frame.png")); }
+    });
+}
+
+/// Remember a key transition (engine calls this)
+pub(crate) fn record_input(key: Key, down: bool) {
+    with_state(|s| {
+        if s.input.len() == MAX_INPUT_EVENTS { s.input.pop_front(); }
+        s.input.push_back((s.frame_no, key, down));
+    });
+}
+
+fn dump(panic_msg: &str) -> Option<PathBuf> {
+    let mut out = None;
+    with_state(|s| {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let dir = s.dir.join(format!("crash-{stamp}"));
+        if std::fs::create_dir_all(&dir).is_err() { return; }
+        let mut log = format!("panic at frame {}: {panic_msg}\n\nrecent log:\n", s.frame_no);
+        for l in &s.log { log.push_str(l); log.push('\n'); }
+        let _ = std::fs::write(dir.join("log.txt"), log);
+        let mut input = String::new();
+        for (frame, key, down) in &s.input {
+            let _ = writeln!(input, "{frame}\t{key:?}\t{}", if *down { "down" } else { "up" });
+        }
+        let _ = std::fs::write(dir.join("input.txt"), input);
+        s.last_dump = Some(dir.clone());
+        out = Some(dir);
+    });
+    out
+}
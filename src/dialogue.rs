@@ -0,0 +1,347 @@
+//! -------- Dialogue: branching conversations from a small Yarn-like script --------
+//!
+//! ```text
+//! // comments start with two slashes
+//! == gate
+//! Guard: Halt! Who goes there?
+//! * A friend. -> friendly if rep >= 10
+//! * None of your business. -> hostile
+//!
+//! == friendly
+//! ! set met_guard 1
+//! Guard: Go on through.
+//!
+//! == hostile
+//! ! call alert_guards north
+//! > The guard reaches for his horn.
+//! -> gate
+//! ```
+//!
+//! `== name` starts a node, `Speaker: text` / `> narration` are lines (the
+//! speaker is a single word of letters, digits or `_`; anything else is
+//! narration), consecutive `* text -> node [if cond]` lines form one choice,
+//! `-> node` jumps, and `! set|add var n` / `! call name args..` run commands. A
+//! node that runs out of steps ends the conversation. Conditions are `var`,
+//! `!var` or `var <op> int`. Jumps that loop back without showing anything are
+//! rejected at parse time, since playing them would never return.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Game variables visible to dialogue conditions and commands (missing = 0)
+pub type Variables = HashMap<String, i64>;
+
+/// Script parse error with its 1-based line number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for DialogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dialogue line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for DialogueError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op { Eq, Ne, Lt, Le, Gt, Ge }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cond {
+    Truthy(String),
+    Falsy(String),
+    Cmp(String, Op, i64),
+}
+
+impl Cond {
+    fn parse(src: &str) -> Option<Cond> {
+        let src = src.trim();
+        if let Some(var) = src.strip_prefix('!') {
+            return Some(Cond::Falsy(var.trim().to_string()));
+        }
+        let parts: Vec<&str> = src.split_whitespace().collect();
+        match parts.as_slice() {
+            [var] => Some(Cond::Truthy(var.to_string())),
+            [var, op, val] => {
+                let op = match *op {
+                    "==" => Op::Eq, "!=" => Op::Ne, "<" => Op::Lt,
+                    "<=" => Op::Le, ">" => Op::Gt, ">=" => Op::Ge,
+                    _ => return None,
+                };
+                Some(Cond::Cmp(var.to_string(), op, val.parse().ok()?))
+            }
+            _ => None,
+        }
+    }
+    fn eval(&self, vars: &Variables) -> bool {
+        let get = |v: &str| vars.get(v).copied().unwrap_or(0);
+        match self {
+            Cond::Truthy(v) => get(v) != 0,
+            Cond::Falsy(v) => get(v) == 0,
+            Cond::Cmp(v, op, rhs) => {
+                let lhs = get(v);
+                match op {
+                    Op::Eq => lhs == *rhs, Op::Ne => lhs != *rhs,
+                    Op::Lt => lhs < *rhs, Op::Le => lhs <= *rhs,
+                    Op::Gt => lhs > *rhs, Op::Ge => lhs >= *rhs,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Choice {
+    text: String,
+    target: String,
+    cond: Option<Cond>,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Line { speaker: Option<String>, text: String },
+    Choices(Vec<Choice>),
+    Jump(String),
+    Set(String, i64),
+    Add(String, i64),
+    Call(String, Vec<String>),
+}
+
+/// A parsed dialogue script (immutable; play it with a `DialogueRunner`)
+#[derive(Debug, Clone, Default)]
+pub struct Dialogue {
+    nodes: HashMap<String, Vec<Step>>,
+}
+
+impl Dialogue {
+    pub fn parse(src: &str) -> Result<Self, DialogueError> {
+        let mut nodes: HashMap<String, Vec<Step>> = HashMap::new();
+        let mut current: Option<String> = None;
+        let mut jumps: Vec<(usize, String)> = Vec::new();
+        // (node, step index) -> source line of each `-> node` step
+        let mut jump_lines: HashMap<(String, usize), usize> = HashMap::new();
+        for (i, raw) in src.lines().enumerate() {
+            let ln = i + 1;
+            let err = |msg: &str| DialogueError { line: ln, msg: msg.to_string() };
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with("//") { continue; }
+
+            if let Some(name) = line.strip_prefix("==") {
+                let name = name.trim();
+                if name.is_empty() { return Err(err("missing node name")); }
+                if nodes.insert(name.to_string(), Vec::new()).is_some() {
+                    return Err(err("duplicate node"));
+                }
+                current = Some(name.to_string());
+                continue;
+            }
+            let Some(node) = current.as_ref() else { return Err(err("step outside of a node")) };
+            let steps = nodes.get_mut(node).unwrap();
+
+            let step = if let Some(rest) = line.strip_prefix('*') {
+                let (text, tail) = rest.split_once("->").ok_or_else(|| err("choice needs '-> node'"))?;
+                let (target, cond) = match tail.split_once(" if ") {
+                    Some((t, c)) => (t, Some(Cond::parse(c).ok_or_else(|| err("bad condition"))?)),
+                    None => (tail, None),
+                };
+                let target = target.trim().to_string();
+                jumps.push((ln, target.clone()));
+                let choice = Choice { text: text.trim().to_string(), target, cond };
+                // consecutive choice lines form a single menu
+                if let Some(Step::Choices(list)) = steps.last_mut() {
+                    list.push(choice);
+                    continue;
+                }
+                Step::Choices(vec![choice])
+            } else if let Some(target) = line.strip_prefix("->") {
+                let target = target.trim().to_string();
+                jumps.push((ln, target.clone()));
+                jump_lines.insert((node.clone(), steps.len()), ln);
+                Step::Jump(target)
+            } else if let Some(cmd) = line.strip_prefix('!') {
+                let mut words = cmd.split_whitespace();
+                match (words.next(), words.next(), words.next()) {
+                    (Some("set"), Some(var), Some(val)) =>
+                        Step::Set(var.to_string(), val.parse().map_err(|_| err("bad number"))?),
+                    (Some("add"), Some(var), Some(val)) =>
+                        Step::Add(var.to_string(), val.parse().map_err(|_| err("bad number"))?),
+                    (Some("call"), Some(name), first) => Step::Call(
+                        name.to_string(),
+                        first.into_iter().chain(words).map(str::to_string).collect(),
+                    ),
+                    _ => return Err(err("unknown command")),
+                }
+            } else if let Some(text) = line.strip_prefix('>') {
+                Step::Line { speaker: None, text: text.trim().to_string() }
+            } else if let Some((speaker, text)) = line.split_once(": ").filter(|(s, _)| is_speaker(s)) {
+                Step::Line { speaker: Some(speaker.trim().to_string()), text: text.trim().to_string() }
+            } else {
+                Step::Line { speaker: None, text: line.to_string() }
+            };
+            steps.push(step);
+        }
+        for (ln, target) in jumps {
+            if !nodes.contains_key(&target) {
+                return Err(DialogueError { line: ln, msg: format!("unknown node '{target}'") });
+            }
+        }
+        check_silent_loops(&nodes, &jump_lines)?;
+        Ok(Self { nodes })
+    }
+    pub fn has_node(&self, name: &str) -> bool { self.nodes.contains_key(name) }
+}
+
+fn is_speaker(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Reject jump cycles that reach no line or menu, which would spin `advance`
+/// forever. From the top of each node only commands, menus whose options are all
+/// conditional (they may all be hidden) and the first jump run without output,
+/// so every node has at most one silent successor and a cycle is found by walking.
+fn check_silent_loops(
+    nodes: &HashMap<String, Vec<Step>>,
+    jump_lines: &HashMap<(String, usize), usize>,
+) -> Result<(), DialogueError> {
+    let mut next: HashMap<&str, (&str, usize)> = HashMap::new();
+    for (name, steps) in nodes {
+        for (i, step) in steps.iter().enumerate() {
+            match step {
+                Step::Line { .. } => break,
+                Step::Choices(list) if list.iter().any(|c| c.cond.is_none()) => break,
+                Step::Jump(target) => {
+                    next.insert(name, (target, jump_lines[&(name.clone(), i)]));
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    // 0 = unvisited, 1 = on the current walk, 2 = known to reach output or the end
+    let mut state: HashMap<&str, u8> = HashMap::new();
+    for start in next.keys() {
+        let mut walk = Vec::new();
+        let mut at = *start;
+        while let Some(&(target, ln)) = next.get(at) {
+            match state.get(at).copied().unwrap_or(0) {
+                2 => break,
+                1 => return Err(DialogueError { line: ln, msg: format!("jump to '{target}' loops without showing anything") }),
+                _ => {}
+            }
+            state.insert(at, 1);
+            walk.push(at);
+            at = target;
+        }
+        for n in walk { state.insert(n, 2); }
+    }
+    Ok(())
+}
+
+/// What the presenter (dialog box, HUD, ...) should show next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogueEvent<'a> {
+    Line { speaker: Option<&'a str>, text: &'a str },
+    /// Options whose conditions passed; answer with `DialogueRunner::choose`
+    Choices(Vec<&'a str>),
+    End,
+}
+
+/// Plays a `Dialogue`, one event per `advance`
+#[derive(Debug, Clone)]
+pub struct DialogueRunner {
+    script: Dialogue,
+    node: Option<String>,
+    pc: usize,
+    // targets of the currently offered choices, in the order they were shown
+    offered: Vec<String>,
+}
+
+impl DialogueRunner {
+    pub fn new(script: Dialogue) -> Self {
+        Self { script, node: None, pc: 0, offered: Vec::new() }
+    }
+    /// Begin at the given node; returns false if it doesn't exist
+    pub fn start(&mut self, node: &str) -> bool {
+        if !self.script.has_node(node) { return false; }
+        self.node = Some(node.to_string());
+        self.pc = 0;
+        self.offered.clear();
+        true
+    }
+    #[inline] pub fn is_running(&self) -> bool { self.node.is_some() }
+    #[inline] pub fn current_node(&self) -> Option<&str> { self.node.as_deref() }
+
+    /// Run commands until the next line, choice menu or the end. `on_call` receives
+    /// `! call` commands so dialogue can trigger gameplay (give items, start fights).
+    pub fn advance(
+        &mut self,
+        vars: &mut Variables,
+        mut on_call: impl FnMut(&str, &[String], &mut Variables),
+    ) -> DialogueEvent<'_> {
+        // index of the step to present once commands/jumps are exhausted
+        let shown = loop {
+            let Some(node) = self.node.as_ref() else { return DialogueEvent::End };
+            let Some(step) = self.script.nodes[node].get(self.pc) else {
+                self.node = None;
+                return DialogueEvent::End;
+            };
+            match step {
+                Step::Line { .. } => {
+                    self.pc += 1;
+                    break self.pc - 1;
+                }
+                Step::Choices(list) => {
+                    // pc stays put until `choose`, so advancing again re-offers the menu
+                    self.offered = list.iter()
+                        .filter(|c| c.cond.as_ref().is_none_or(|cond| cond.eval(vars)))
+                        .map(|c| c.target.clone())
+                        .collect();
+                    if self.offered.is_empty() {
+                        self.pc += 1;
+                        continue;
+                    }
+                    break self.pc;
+                }
+                Step::Jump(target) => {
+                    self.node = Some(target.clone());
+                    self.pc = 0;
+                }
+                Step::Set(var, val) => {
+                    vars.insert(var.clone(), *val);
+                    self.pc += 1;
+                }
+                Step::Add(var, val) => {
+                    *vars.entry(var.clone()).or_insert(0) += *val;
+                    self.pc += 1;
+                }
+                Step::Call(name, args) => {
+                    on_call(name, args, vars);
+                    self.pc += 1;
+                }
+            }
+        };
+        let node = self.node.as_ref().unwrap();
+        match &self.script.nodes[node][shown] {
+            Step::Line { speaker, text } => DialogueEvent::Line { speaker: speaker.as_deref(), text },
+            Step::Choices(list) => DialogueEvent::Choices(
+                list.iter()
+                    .filter(|c| c.cond.as_ref().is_none_or(|cond| cond.eval(vars)))
+                    .map(|c| c.text.as_str())
+                    .collect(),
+            ),
+            _ => unreachable!("only lines and menus are presented"),
+        }
+    }
+
+    /// Pick one of the options from the last `DialogueEvent::Choices`
+    pub fn choose(&mut self, index: usize) -> bool {
+        let Some(target) = self.offered.get(index).cloned() else { return false };
+        self.offered.clear();
+        self.node = Some(target);
+        self.pc = 0;
+        true
+    }
+}
@@ -0,0 +1,145 @@
+//! -------- Display: fullscreen modes, monitors, vsync and icon --------
+//!
+//! Piston only knows "fullscreen or not"; these go straight to the winit window
+//! underneath the Piston/glutin stack.
+
+use crate::{PixError, PixResult};
+use glutin::context::PossiblyCurrentContext;
+use glutin::surface::{GlSurface, Surface, SwapInterval, WindowSurface};
+use std::num::NonZeroU32;
+use winit::dpi::PhysicalPosition;
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Icon, Window};
+
+/// How the canvas is scaled into the window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Largest whole-number multiple that fits, centered (crispest, may waste space)
+    #[default]
+    IntegerOnly,
+    /// Largest fractional scale that fits, keeping aspect; nearest sampling
+    /// makes some pixel rows/columns one screen pixel wider than others
+    FitPreserveAspect,
+    /// Fill the whole window, ignoring aspect
+    Stretch,
+    /// Fit like `FitPreserveAspect`, but prescale by the integer part with
+    /// nearest sampling and cover the rest with bilinear: even pixels, soft edges
+    SharpBilinear,
+}
+
+impl ScaleMode {
+    /// Screen pixels per canvas pixel (x, y) for a window of `win` and canvas of
+    /// `canvas`. `pixel_aspect` is a canvas pixel's width/height (1.0 = square);
+    /// the integer step then applies vertically and x follows the aspect.
+    pub fn scale(self, win: (u32, u32), canvas: (u32, u32), pixel_aspect: f64) -> (f64, f64) {
+        let par = if pixel_aspect > 0.0 { pixel_aspect } else { 1.0 };
+        let sx = win.0 as f64 / (canvas.0.max(1) as f64 * par);
+        let sy = win.1 as f64 / canvas.1.max(1) as f64;
+        match self {
+            ScaleMode::IntegerOnly => {
+                let s = sx.min(sy).floor().max(1.0);
+                (s * par, s)
+            }
+            ScaleMode::FitPreserveAspect | ScaleMode::SharpBilinear => {
+                let s = sx.min(sy);
+                (s * par, s)
+            }
+            ScaleMode::Stretch => (sx * par, sy),
+        }
+    }
+}
+
+/// One connected display, as listed by `PixEngine::monitors`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// Pass to `EngineConfig::monitor` / `PixEngine::set_monitor`
+    pub index: usize,
+    pub name: Option<String>,
+    /// Physical pixels
+    pub size: (u32, u32),
+    /// Top-left on the virtual desktop
+    pub position: (i32, i32),
+    pub scale_factor: f64,
+    pub primary: bool,
+}
+
+/// How `PixEngine::set_fullscreen` takes over the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// Undecorated window covering the monitor at its desktop resolution
+    /// ("fullscreen desktop"); alt-tabs instantly and plays well with multiple monitors
+    #[default]
+    Borderless,
+    /// Switches the monitor's video mode (largest resolution, then highest refresh)
+    Exclusive,
+}
+
+pub(crate) fn monitors(window: &Window) -> Vec<MonitorInfo> {
+    let primary = window.primary_monitor();
+    window.available_monitors().enumerate().map(|(index, m)| {
+        let (size, pos) = (m.size(), m.position());
+        MonitorInfo {
+            index,
+            name: m.name(),
+            size: (size.width, size.height),
+            position: (pos.x, pos.y),
+            scale_factor: m.scale_factor(),
+            primary: primary.as_ref() == Some(&m),
+        }
+    }).collect()
+}
+
+/// Monitor `index`, falling back to the window's current then the primary one
+fn pick_monitor(window: &Window, index: Option<usize>) -> Option<MonitorHandle> {
+    index.and_then(|i| window.available_monitors().nth(i))
+        .or_else(|| window.current_monitor())
+        .or_else(|| window.primary_monitor())
+}
+
+/// Center the (windowed) window on monitor `index`; unknown indices are ignored
+pub(crate) fn move_to_monitor(window: &Window, index: usize) {
+    let Some(m) = window.available_monitors().nth(index) else { return };
+    let (mp, ms, ws) = (m.position(), m.size(), window.outer_size());
+    let x = mp.x + (ms.width as i32 - ws.width as i32) / 2;
+    let y = mp.y + (ms.height as i32 - ws.height as i32) / 2;
+    window.set_outer_position(PhysicalPosition::new(x, y));
+}
+
+/// Enter `mode` on monitor `index` (default: the window's current one), or leave fullscreen
+pub(crate) fn set_fullscreen(window: &Window, on: bool, mode: FullscreenMode, index: Option<usize>) {
+    if !on {
+        window.set_fullscreen(None);
+        return;
+    }
+    let monitor = pick_monitor(window, index);
+    let fs = match mode {
+        FullscreenMode::Borderless => Fullscreen::Borderless(monitor),
+        FullscreenMode::Exclusive => {
+            let best = monitor.as_ref().and_then(|m| {
+                m.video_modes().max_by_key(|v| {
+                    let s = v.size();
+                    (s.width as u64 * s.height as u64, v.refresh_rate_millihertz(), v.bit_depth())
+                })
+            });
+            match best {
+                Some(v) => Fullscreen::Exclusive(v),
+                None => Fullscreen::Borderless(monitor), // no mode list (e.g. Wayland)
+            }
+        }
+    };
+    window.set_fullscreen(Some(fs));
+}
+
+/// 0 = don't wait (tearing, lowest latency), n = swap every n-th vblank
+pub(crate) fn set_swap_interval(surface: &Surface<WindowSurface>, ctx: &PossiblyCurrentContext, n: u32) -> PixResult<()> {
+    let interval = NonZeroU32::new(n).map_or(SwapInterval::DontWait, SwapInterval::Wait);
+    surface.set_swap_interval(ctx, interval).map_err(|e| PixError::Window(format!("swap interval {n}: {e}")))
+}
+
+/// Taskbar / title bar icon (ignored on platforms without one, e.g. macOS)
+pub(crate) fn set_icon(window: &Window, icon: &image::RgbaImage) -> PixResult<()> {
+    let icon = Icon::from_rgba(icon.as_raw().clone(), icon.width(), icon.height())
+        .map_err(|e| PixError::Window(format!("window icon: {e}")))?;
+    window.set_window_icon(Some(icon));
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! -------- Error: engine-wide error type --------
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum PixError {
+    /// Image file missing, unreadable or undecodable
+    Image(image::ImageError),
+    Io(std::io::Error),
+    /// Asset decoded fine but has the wrong layout (e.g. LUT strip dimensions)
+    Format(String),
+    /// GPU texture creation or upload failed
+    Texture(String),
+    /// Window or GL context creation failed
+    Window(String),
+}
+
+pub type PixResult<T> = Result<T, PixError>;
+
+impl fmt::Display for PixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PixError::Image(e) => write!(f, "image error: {e}"),
+            PixError::Io(e) => write!(f, "i/o error: {e}"),
+            PixError::Format(e) => write!(f, "format error: {e}"),
+            PixError::Texture(e) => write!(f, "texture error: {e}"),
+            PixError::Window(e) => write!(f, "window error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PixError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PixError::Image(e) => Some(e),
+            PixError::Io(e) => Some(e),
+            PixError::Format(_) | PixError::Texture(_) | PixError::Window(_) => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for PixError {
+    fn from(e: image::ImageError) -> Self { PixError::Image(e) }
+}
+
+impl From<std::io::Error> for PixError {
+    fn from(e: std::io::Error) -> Self { PixError::Io(e) }
+}
@@ -0,0 +1,104 @@
+//! -------- Fills: dithered gradients and 1-bit patterns --------
+//!
+//! Two-color fills for the low-color look: every pixel is one of the two
+//! colors, chosen by an 8x8 ordered (Bayer) threshold or an 8x8 stipple
+//! pattern. Both are anchored to buffer coordinates, so neighbouring fills line up.
+
+use crate::{PixelBuffer, Rect};
+
+const BAYER8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Direction of a linear gradient, from `c0` to `c1`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GradientDir {
+    /// Left to right
+    #[default]
+    Horizontal,
+    /// Top to bottom
+    Vertical,
+}
+
+/// 8x8 1-bit stipple: one byte per row, most significant bit = leftmost pixel
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pattern(pub [u8; 8]);
+
+impl Pattern {
+    pub const SOLID: Pattern = Pattern([0xFF; 8]);
+    pub const CHECKER: Pattern = Pattern([0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55]);
+    /// Every fourth pixel (25% coverage)
+    pub const SPARSE: Pattern = Pattern([0x88, 0x00, 0x22, 0x00, 0x88, 0x00, 0x22, 0x00]);
+    /// 75% coverage (inverse of `SPARSE`)
+    pub const DENSE: Pattern = Pattern([0x77, 0xFF, 0xDD, 0xFF, 0x77, 0xFF, 0xDD, 0xFF]);
+    /// Stripes falling to the right (`\`)
+    pub const DIAGONAL: Pattern = Pattern([0x88, 0x44, 0x22, 0x11, 0x88, 0x44, 0x22, 0x11]);
+    /// Stripes rising to the right (`/`)
+    pub const ANTI_DIAGONAL: Pattern = Pattern([0x11, 0x22, 0x44, 0x88, 0x11, 0x22, 0x44, 0x88]);
+    pub const HORIZONTAL: Pattern = Pattern([0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00]);
+    pub const VERTICAL: Pattern = Pattern([0xAA; 8]);
+
+    /// Is the bit for buffer pixel (x, y) set?
+    #[inline] pub fn get(&self, x: i32, y: i32) -> bool {
+        self.0[(y & 7) as usize] & (0x80 >> (x & 7)) != 0
+    }
+    #[inline] pub fn inverted(self) -> Pattern { Pattern(self.0.map(|r| !r)) }
+}
+
+/// `c1` where the 0.0..=1.0 blend `t` beats the Bayer threshold at (x, y), else `c0`
+#[inline]
+fn dither(x: i32, y: i32, t: f32, c0: [u8; 4], c1: [u8; 4]) -> [u8; 4] {
+    let level = (t.clamp(0.0, 1.0) * 64.0).round() as u8;
+    if BAYER8[(y & 7) as usize][(x & 7) as usize] < level { c1 } else { c0 }
+}
+
+impl PixelBuffer {
+    /// Set every pixel of `r` (clipped, mask-aware) to `f(x, y)`
+    pub(crate) fn fill_rect_with(&mut self, r: Rect, mut f: impl FnMut(i32, i32) -> [u8; 4]) {
+        let Some(r) = r.intersect(&Rect::new(0, 0, self.w, self.h)) else { return };
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                let c = f(x, y);
+                self.write(x as u32, y as u32, c);
+            }
+        }
+    }
+
+    /// Linear gradient from `c0` to `c1` across `r`, ordered-dithered
+    pub fn fill_rect_gradient(&mut self, r: Rect, c0: [u8; 4], c1: [u8; 4], dir: GradientDir) {
+        let span = match dir {
+            GradientDir::Horizontal => r.w,
+            GradientDir::Vertical => r.h,
+        }.saturating_sub(1).max(1) as f32;
+        self.fill_rect_with(r, |x, y| {
+            let p = match dir {
+                GradientDir::Horizontal => x - r.x,
+                GradientDir::Vertical => y - r.y,
+            };
+            dither(x, y, p as f32 / span, c0, c1)
+        });
+    }
+
+    /// Radial gradient: `c0` at (cx, cy) fading to `c1` at `radius` and beyond,
+    /// filling the circle's bounding box
+    pub fn fill_radial_gradient(&mut self, cx: i32, cy: i32, radius: u32, c0: [u8; 4], c1: [u8; 4]) {
+        let r = radius.max(1) as f32;
+        let bounds = Rect::new(cx - radius as i32, cy - radius as i32, radius * 2 + 1, radius * 2 + 1);
+        self.fill_rect_with(bounds, |x, y| {
+            let d = (((x - cx) * (x - cx) + (y - cy) * (y - cy)) as f32).sqrt();
+            dither(x, y, d / r, c0, c1)
+        });
+    }
+
+    /// Fill `r` with `fg` where `pattern` is set and `bg` elsewhere
+    pub fn fill_rect_pattern(&mut self, r: Rect, pattern: Pattern, fg: [u8; 4], bg: [u8; 4]) {
+        self.fill_rect_with(r, |x, y| if pattern.get(x, y) { fg } else { bg });
+    }
+}
@@ -0,0 +1,46 @@
+//! -------- Filters: whole-buffer color effects --------
+//!
+//! Cheap integer-math passes for pause screens, flashbacks and damage effects.
+//! Like the other post steps they cover the whole buffer, ignore the clip,
+//! mask and blend mode, and keep alpha.
+
+use crate::PixelBuffer;
+
+#[inline]
+fn luma(p: &[u8]) -> u32 {
+    (p[0] as u32 * 77 + p[1] as u32 * 150 + p[2] as u32 * 29) >> 8
+}
+
+impl PixelBuffer {
+    /// Luminance-weighted grayscale
+    pub fn grayscale(&mut self) {
+        self.for_each_pixel(|p| {
+            let l = luma(p) as u8;
+            p[..3].fill(l);
+        });
+    }
+    /// Warm brown monochrome (the classic Microsoft sepia matrix, /256)
+    pub fn sepia(&mut self) {
+        self.for_each_pixel(|p| {
+            let (r, g, b) = (p[0] as u32, p[1] as u32, p[2] as u32);
+            p[0] = ((r * 101 + g * 197 + b * 48) >> 8).min(255) as u8;
+            p[1] = ((r * 89 + g * 176 + b * 43) >> 8).min(255) as u8;
+            p[2] = ((r * 70 + g * 137 + b * 34) >> 8).min(255) as u8;
+        });
+    }
+    /// Photographic negative
+    pub fn invert(&mut self) {
+        self.for_each_pixel(|p| {
+            for c in &mut p[..3] { *c = 255 - *c; }
+        });
+    }
+    /// Reduce each channel to `levels` evenly spaced values (2..=255; less is a no-op)
+    pub fn posterize(&mut self, levels: u8) {
+        if levels < 2 { return; }
+        let n = levels as u32 - 1;
+        let table: [u8; 256] = std::array::from_fn(|v| (((v as u32 * n + 127) / 255) * 255 / n) as u8);
+        self.for_each_pixel(|p| {
+            for c in &mut p[..3] { *c = table[*c as usize]; }
+        });
+    }
+}
@@ -0,0 +1,251 @@
+//! -------- Gamepad: analog stick conditioning --------
+//!
+//! Raw stick values drift around the center and rarely reach ±1.0. Per stick,
+//! `StickSettings` applies calibration (center/range), a deadzone, an outer
+//! saturation zone and a response curve. `Gamepads` keeps the latest raw axes
+//! from `Scene::gamepad_axis` and hands out processed sticks. Settings
+//! round-trip through a small `key=value` text format for saving alongside the
+//! game's other input settings. `Gamepads::rumble` (or `Context::rumble` per
+//! player) is the force-feedback hook; it's a no-op on back ends that can't vibrate.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Deadzone {
+    None,
+    /// Each axis ignored independently below the threshold (snaps to 8 directions near center)
+    Axial(f32),
+    /// Ignore the stick while its length is below the threshold; rescale the rest to 0..1
+    Radial(f32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResponseCurve {
+    Linear,
+    /// magnitude^exp: >1 gives finer control near center, <1 snappier
+    Power(f32),
+}
+
+/// Maps one raw axis to -1.0..=1.0 around a measured center
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisCalibration {
+    pub center: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for AxisCalibration {
+    fn default() -> Self { Self { center: 0.0, min: -1.0, max: 1.0 } }
+}
+
+impl AxisCalibration {
+    #[inline]
+    pub fn normalize(&self, raw: f32) -> f32 {
+        let v = raw - self.center;
+        let span = if v < 0.0 { self.center - self.min } else { self.max - self.center };
+        if span <= f32::EPSILON { 0.0 } else { (v / span).clamp(-1.0, 1.0) }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StickSettings {
+    pub deadzone: Deadzone,
+    /// Magnitude at which output reaches 1.0 (worn sticks rarely hit the rim)
+    pub outer: f32,
+    pub curve: ResponseCurve,
+    pub x: AxisCalibration,
+    pub y: AxisCalibration,
+    pub invert_y: bool,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: Deadzone::Radial(0.15),
+            outer: 0.95,
+            curve: ResponseCurve::Linear,
+            x: AxisCalibration::default(),
+            y: AxisCalibration::default(),
+            invert_y: false,
+        }
+    }
+}
+
+impl StickSettings {
+    /// Raw axis pair -> conditioned stick, length <= 1.0
+    pub fn apply(&self, raw_x: f32, raw_y: f32) -> (f32, f32) {
+        let (mut x, mut y) = (self.x.normalize(raw_x), self.y.normalize(raw_y));
+        if self.invert_y { y = -y; }
+        let outer = self.outer.clamp(0.01, 1.0);
+        // remap magnitude from [inner, outer] to [0, 1]
+        let remap = |m: f32, inner: f32| ((m - inner) / (outer - inner).max(f32::EPSILON)).clamp(0.0, 1.0);
+        match self.deadzone {
+            Deadzone::None => {}
+            Deadzone::Axial(t) => {
+                let axis = |v: f32| if v.abs() < t { 0.0 } else { v.signum() * remap(v.abs(), t) };
+                x = axis(x);
+                y = axis(y);
+            }
+            Deadzone::Radial(t) => {
+                let m = (x * x + y * y).sqrt();
+                if m < t { return (0.0, 0.0); }
+                let k = remap(m, t) / m;
+                x *= k;
+                y *= k;
+            }
+        }
+        let m = (x * x + y * y).sqrt();
+        if m <= 0.0 { return (0.0, 0.0); }
+        let target = match self.curve {
+            ResponseCurve::Linear => m.min(1.0),
+            ResponseCurve::Power(e) => m.min(1.0).powf(e.max(0.01)),
+        };
+        (x * target / m, y * target / m)
+    }
+
+    /// Serialize as `key=value` lines
+    pub fn to_config(&self) -> String {
+        let mut s = String::new();
+        let _ = match self.deadzone {
+            Deadzone::None => writeln!(s, "deadzone=none"),
+            Deadzone::Axial(t) => writeln!(s, "deadzone=axial:{t}"),
+            Deadzone::Radial(t) => writeln!(s, "deadzone=radial:{t}"),
+        };
+        let _ = writeln!(s, "outer={}", self.outer);
+        let _ = match self.curve {
+            ResponseCurve::Linear => writeln!(s, "curve=linear"),
+            ResponseCurve::Power(e) => writeln!(s, "curve=power:{e}"),
+        };
+        let _ = writeln!(s, "x={},{},{}", self.x.center, self.x.min, self.x.max);
+        let _ = writeln!(s, "y={},{},{}", self.y.center, self.y.min, self.y.max);
+        let _ = writeln!(s, "invert_y={}", self.invert_y);
+        s
+    }
+    /// Parse `to_config` output; unknown keys are ignored, missing keys keep defaults
+    pub fn from_config(src: &str) -> Option<Self> {
+        let mut out = Self::default();
+        for line in src.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (key, val) = line.split_once('=')?;
+            let val = val.trim();
+            let tagged = |v: &str| -> Option<(String, Option<f32>)> {
+                match v.split_once(':') {
+                    Some((tag, n)) => Some((tag.trim().to_string(), Some(n.trim().parse().ok()?))),
+                    None => Some((v.to_string(), None)),
+                }
+            };
+            let axis = |v: &str| -> Option<AxisCalibration> {
+                let n: Vec<f32> = v.split(',').map(|p| p.trim().parse()).collect::<Result<_, _>>().ok()?;
+                match n.as_slice() {
+                    [center, min, max] => Some(AxisCalibration { center: *center, min: *min, max: *max }),
+                    _ => None,
+                }
+            };
+            match key.trim() {
+                "deadzone" => out.deadzone = match tagged(val)? {
+                    (t, None) if t == "none" => Deadzone::None,
+                    (t, Some(n)) if t == "axial" => Deadzone::Axial(n),
+                    (t, Some(n)) if t == "radial" => Deadzone::Radial(n),
+                    _ => return None,
+                },
+                "outer" => out.outer = val.parse().ok()?,
+                "curve" => out.curve = match tagged(val)? {
+                    (t, None) if t == "linear" => ResponseCurve::Linear,
+                    (t, Some(e)) if t == "power" => ResponseCurve::Power(e),
+                    _ => return None,
+                },
+                "x" => out.x = axis(val)?,
+                "y" => out.y = axis(val)?,
+                "invert_y" => out.invert_y = val.parse().ok()?,
+                _ => {}
+            }
+        }
+        Some(out)
+    }
+}
+
+/// Two-phase calibration: let the stick rest, then sweep it around the rim
+#[derive(Clone, Debug, Default)]
+pub struct Calibrator {
+    rest: Vec<(f32, f32)>,
+    min: (f32, f32),
+    max: (f32, f32),
+    swept: bool,
+}
+
+impl Calibrator {
+    pub fn new() -> Self { Self::default() }
+    /// Feed raw values while the player isn't touching the stick
+    pub fn sample_rest(&mut self, x: f32, y: f32) { self.rest.push((x, y)); }
+    /// Feed raw values while the player rotates the stick along its edge
+    pub fn sample_sweep(&mut self, x: f32, y: f32) {
+        if !self.swept {
+            self.min = (x, y);
+            self.max = (x, y);
+            self.swept = true;
+        }
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+    }
+    /// Resulting calibration plus a suggested radial deadzone (rest noise + margin)
+    pub fn finish(&self) -> Option<(AxisCalibration, AxisCalibration, f32)> {
+        if self.rest.is_empty() || !self.swept { return None; }
+        let n = self.rest.len() as f32;
+        let cx = self.rest.iter().map(|p| p.0).sum::<f32>() / n;
+        let cy = self.rest.iter().map(|p| p.1).sum::<f32>() / n;
+        let x = AxisCalibration { center: cx, min: self.min.0, max: self.max.0 };
+        let y = AxisCalibration { center: cy, min: self.min.1, max: self.max.1 };
+        let noise = self.rest.iter()
+            .map(|&(rx, ry)| { let (nx, ny) = (x.normalize(rx), y.normalize(ry)); (nx * nx + ny * ny).sqrt() })
+            .fold(0.0, f32::max);
+        Some((x, y, (noise + 0.05).min(0.5)))
+    }
+}
+
+/// Which raw axes form a stick (SDL order: left 0/1, right 2/3)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StickAxes { pub x: u8, pub y: u8 }
+
+impl StickAxes {
+    pub const LEFT: StickAxes = StickAxes { x: 0, y: 1 };
+    pub const RIGHT: StickAxes = StickAxes { x: 2, y: 3 };
+}
+
+/// Latest raw axis values per controller, with conditioned stick queries
+#[derive(Clone, Debug, Default)]
+pub struct Gamepads {
+    raw: HashMap<(u32, u8), f32>,
+    settings: HashMap<u32, [StickSettings; 2]>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self { Self::default() }
+    /// Call from `Scene::gamepad_axis`
+    pub fn handle_axis(&mut self, id: u32, axis: u8, position: f64) {
+        self.raw.insert((id, axis), position as f32);
+    }
+    #[inline] pub fn raw_axis(&self, id: u32, axis: u8) -> f32 {
+        self.raw.get(&(id, axis)).copied().unwrap_or(0.0)
+    }
+    /// Settings for stick 0 (left) or 1 (right) of a controller
+    pub fn settings_mut(&mut self, id: u32, stick: usize) -> &mut StickSettings {
+        &mut self.settings.entry(id).or_insert([StickSettings::default(); 2])[stick.min(1)]
+    }
+    pub fn left_stick(&self, id: u32) -> (f32, f32) { self.stick(id, 0, StickAxes::LEFT) }
+    pub fn right_stick(&self, id: u32) -> (f32, f32) { self.stick(id, 1, StickAxes::RIGHT) }
+    /// Conditioned stick from arbitrary axes, using the settings of slot `stick`
+    pub fn stick(&self, id: u32, stick: usize, axes: StickAxes) -> (f32, f32) {
+        let settings = self.settings.get(&id).map(|s| s[stick.min(1)]).unwrap_or_default();
+        settings.apply(self.raw_axis(id, axes.x), self.raw_axis(id, axes.y))
+    }
+    /// Whether controller `id` can vibrate. Always false for now: the window
+    /// back end (glutin/winit) reports axes and buttons but has no force feedback.
+    #[inline] pub fn supports_rumble(&self, _id: u32) -> bool { false }
+    /// Vibrate controller `id` at `strength` (0.0..=1.0) for `duration`, e.g. on
+    /// impacts. Returns whether it took effect; safe to call unconditionally,
+    /// since unsupported devices just ignore it.
+    pub fn rumble(&mut self, id: u32, _strength: f32, _duration: Duration) -> bool {
+        self.supports_rumble(id)
+    }
+}
@@ -0,0 +1,145 @@
+//! -------- Hot reload: swap scene code from a rebuilt dylib --------
+//!
+//! Dev-only (feature `hot-reload`). Build the game's scene as a `dylib` crate
+//! that exports a constructor with `export_scene!`, then run it through a
+//! `HotReloadScene`:
+//!
+//! ```ignore
+//! // game_logic/src/lib.rs  (crate-type = ["dylib"])
+//! pixel_engine::export_scene!(|state: &[u8]| MyScene::restore(state));
+//!
+//! // game/src/main.rs
+//! let scene = HotReloadScene::new("target/debug/libgame_logic.so")?;
+//! PixEngine::new(1280, 720, "dev", scene)?.run()?;
+//! ```
+//!
+//! When the library file changes (e.g. `cargo build -p game_logic` in another
+//! terminal) the current scene's `Scene::save_state` bytes are handed to the
+//! new library's constructor, so gameplay continues where it was, and the
+//! engine calls the new scene's `on_load` to reload its assets. Host and
+//! library must be built by the same compiler with the same `pixel_engine`.
+
+use crate::{Assets, Context, PixelBuffer, Scene};
+use crate::layers::Compositor;
+use crate::scenes::SceneChange;
+use piston_window::Key;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::SystemTime;
+
+/// Symbol generated by `export_scene!`
+pub const CREATE_SYMBOL: &[u8] = b"pix_create_scene";
+/// Seconds between library file checks
+const POLL_INTERVAL: f64 = 0.5;
+
+type CreateFn = fn(&[u8]) -> Box<dyn Scene>;
+
+struct Loaded {
+    // field order matters: the scene's code lives in `lib`, so drop it first,
+    // and the shadow copy can only be deleted once the library is unloaded
+    scene: Box<dyn Scene>,
+    _lib: libloading::Library,
+    _copy: TempFile,
+}
+
+/// Shadow copy of the library, removed on drop
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) { let _ = std::fs::remove_file(&self.0); }
+}
+
+/// Scene proxy that reloads its implementation when the dylib is rebuilt
+pub struct HotReloadScene {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    since_poll: f64,
+    // replaced only once the new library loaded, so a failed reload keeps the old one
+    loaded: Loaded,
+    /// Swapped since the engine last ran `on_load`
+    reloaded: bool,
+}
+
+impl HotReloadScene {
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let modified = std::fs::metadata(&path)?.modified().ok();
+        let loaded = load(&path, &[])?;
+        Ok(Self { path, modified, since_poll: 0.0, loaded, reloaded: false })
+    }
+    fn scene(&self) -> &dyn Scene { self.loaded.scene.as_ref() }
+    fn scene_mut(&mut self) -> &mut dyn Scene { self.loaded.scene.as_mut() }
+
+    /// Reload now if the library changed; returns true if code was swapped.
+    /// The engine runs the new scene's `on_load` after the current fixed update.
+    pub fn poll(&mut self) -> bool {
+        let Ok(modified) = std::fs::metadata(&self.path).and_then(|m| m.modified()) else { return false };
+        if Some(modified) == self.modified { return false; }
+        let state = self.scene().save_state();
+        // load first: if the build is still being written, keep running the old code
+        let next = match load(&self.path, &state) {
+            Ok(l) => l,
+            Err(e) => {
+                crate::crash::log(format!("hot reload of {} failed: {e}", self.path.display()));
+                return false;
+            }
+        };
+        self.modified = Some(modified);
+        self.loaded = next;
+        self.reloaded = true;
+        true
+    }
+}
+
+fn load(path: &Path, state: &[u8]) -> io::Result<Loaded> {
+    // Load a uniquely named copy: the OS may cache by path and the original gets
+    // overwritten by the next build while we still hold it open.
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("dylib");
+    // removed again on every error path below (the library, if any, drops first)
+    let copy = TempFile(std::env::temp_dir().join(format!("pix-hot-{}-{n}.{ext}", std::process::id())));
+    std::fs::copy(path, &copy.0)?;
+    let to_io = |e: libloading::Error| io::Error::other(e.to_string());
+    // SAFETY: the library is a scene dylib built for this host (see module docs);
+    // the returned scene is dropped before the library (field order in `Loaded`).
+    unsafe {
+        let lib = libloading::Library::new(&copy.0).map_err(to_io)?;
+        let create: CreateFn = *lib.get::<CreateFn>(CREATE_SYMBOL).map_err(to_io)?;
+        let scene = create(state);
+        Ok(Loaded { scene, _lib: lib, _copy: copy })
+    }
+}
+
+impl Scene for HotReloadScene {
+    fn update(&mut self, ctx: &mut Context, fb: &mut PixelBuffer) {
+        self.since_poll += ctx.dt();
+        if self.since_poll >= POLL_INTERVAL {
+            self.since_poll = 0.0;
+            self.poll();
+        }
+        self.scene_mut().update(ctx, fb);
+    }
+    fn draw(&self, ctx: &Context, fb: &mut PixelBuffer) { self.scene().draw(ctx, fb); }
+    fn key_event(&mut self, key: Key, down: bool) { self.scene_mut().key_event(key, down); }
+    fn gamepad_button(&mut self, id: u32, button: u8, down: bool) { self.scene_mut().gamepad_button(id, button, down); }
+    fn gamepad_axis(&mut self, id: u32, axis: u8, position: f64) { self.scene_mut().gamepad_axis(id, axis, position); }
+    fn on_load(&mut self, assets: &mut Assets) {
+        self.reloaded = false;
+        self.scene_mut().on_load(assets);
+    }
+    fn take_reload(&mut self) -> bool { std::mem::take(&mut self.reloaded) || self.scene_mut().take_reload() }
+    fn draw_layers(&self, layers: &mut Compositor) { self.scene().draw_layers(layers); }
+    fn subpixel_offset(&self) -> (f64, f64) { self.scene().subpixel_offset() }
+    fn time_scale(&self) -> f64 { self.scene().time_scale() }
+    fn update_unscaled(&mut self, ctx: &mut Context) { self.scene_mut().update_unscaled(ctx); }
+    fn save_state(&self) -> Vec<u8> { self.scene().save_state() }
+    fn take_scene_change(&mut self) -> Option<SceneChange> { self.scene_mut().take_scene_change() }
+    fn is_overlay(&self) -> bool { self.scene().is_overlay() }
+    fn canvas_resized(&mut self, w: u32, h: u32) { self.scene_mut().canvas_resized(w, h); }
+    fn time_dropped(&mut self, seconds: f64) { self.scene_mut().time_dropped(seconds); }
+    fn on_focus_lost(&mut self) { self.scene_mut().on_focus_lost(); }
+    fn on_focus_gained(&mut self) { self.scene_mut().on_focus_gained(); }
+    fn on_resize(&mut self, w: u32, h: u32) { self.scene_mut().on_resize(w, h); }
+}
@@ -0,0 +1,207 @@
+//! -------- I18n: per-language string tables --------
+//!
+//! One file per language (`lang/en.toml`, `lang/de.toml`, ...) using a flat TOML
+//! subset: `key = "value"` pairs, optional `[section]` headers that prefix keys
+//! (`[menu]` + `start = ".."` -> `menu.start`), and `#` comments, on their own
+//! line or after a value. Values may hold `{name}` placeholders filled by `tr_with`.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// String tables for every loaded language plus the active/fallback selection
+#[derive(Debug, Clone, Default)]
+pub struct I18n {
+    tables: HashMap<String, HashMap<String, String>>,
+    current: String,
+    fallback: String,
+}
+
+impl I18n {
+    /// `fallback` is consulted when the active language lacks a key
+    pub fn new(fallback: &str) -> Self {
+        Self { tables: HashMap::new(), current: fallback.to_string(), fallback: fallback.to_string() }
+    }
+    /// Parse a string table and register (or extend) language `lang`
+    pub fn add_language(&mut self, lang: &str, src: &str) -> io::Result<()> {
+        let parsed = parse_table(src)?;
+        self.tables.entry(lang.to_string()).or_default().extend(parsed);
+        Ok(())
+    }
+    /// Load every `<lang>.toml` in `dir`; returns the languages found
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> io::Result<Vec<String>> {
+        let mut langs = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("toml") { continue; }
+            let Some(lang) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            self.add_language(lang, &std::fs::read_to_string(&path)?)?;
+            langs.push(lang.to_string());
+        }
+        langs.sort();
+        Ok(langs)
+    }
+    /// Switch the active language at runtime; false if it was never loaded
+    pub fn set_language(&mut self, lang: &str) -> bool {
+        if !self.tables.contains_key(lang) { return false; }
+        self.current = lang.to_string();
+        true
+    }
+    #[inline] pub fn language(&self) -> &str { &self.current }
+    pub fn languages(&self) -> Vec<&str> {
+        let mut v: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+        v.sort_unstable();
+        v
+    }
+    /// Raw lookup: active language, then fallback
+    pub fn get(&self, key: &str) -> Option<&str> {
+        [&self.current, &self.fallback].into_iter()
+            .find_map(|lang| self.tables.get(lang)?.get(key))
+            .map(String::as_str)
+    }
+    /// Translate `key`; a missing key returns the key itself so gaps are visible on screen
+    pub fn tr(&self, key: &str) -> String {
+        self.get(key).unwrap_or(key).to_string()
+    }
+    /// Translate and substitute `{name}` placeholders (`{{` / `}}` are literal braces)
+    pub fn tr_with(&self, key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        interpolate(self.get(key).unwrap_or(key), args)
+    }
+    /// Every distinct char used by the active language, for building/checking font glyph sets
+    pub fn charset(&self) -> Vec<char> {
+        distinct_chars(self.tables.get(&self.current).into_iter())
+    }
+    /// Every distinct char used by any loaded language, for pre-baking one font
+    /// atlas that covers every locale
+    pub fn charset_all(&self) -> Vec<char> {
+        distinct_chars(self.tables.values())
+    }
+}
+
+fn distinct_chars<'a>(tables: impl Iterator<Item = &'a HashMap<String, String>>) -> Vec<char> {
+    let mut chars: Vec<char> = tables.flat_map(|t| t.values()).flat_map(|s| s.chars()).collect();
+    chars.sort_unstable();
+    chars.dedup();
+    chars
+}
+
+/// -------- Global instance for the `tr()` shorthand --------
+static GLOBAL: RwLock<Option<I18n>> = RwLock::new(None);
+
+/// Install the table set used by the free `tr`/`tr_with` functions
+pub fn set_global(i18n: I18n) {
+    *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = Some(i18n);
+}
+
+/// Switch the global language; false if not loaded (or nothing installed)
+pub fn set_global_language(lang: &str) -> bool {
+    GLOBAL.write().unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .is_some_and(|i| i.set_language(lang))
+}
+
+/// Translate with the global tables (returns `key` if none are installed)
+pub fn tr(key: &str) -> String {
+    match GLOBAL.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(i) => i.tr(key),
+        None => key.to_string(),
+    }
+}
+
+/// Translate with placeholders using the global tables
+pub fn tr_with(key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    match GLOBAL.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(i) => i.tr_with(key, args),
+        None => interpolate(key, args),
+    }
+}
+
+fn interpolate(template: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+        } else if let (true, Some(end)) = (tail.starts_with('{'), tail.find('}')) {
+            let name = &tail[1..end];
+            match args.iter().find(|(n, _)| *n == name) {
+                Some((_, v)) => out.push_str(&v.to_string()),
+                None => out.push_str(&tail[..=end]), // leave unknown placeholders as-is
+            }
+            rest = &tail[end + 1..];
+        } else {
+            out.push_str(&tail[..1]);
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn parse_table(src: &str) -> io::Result<HashMap<String, String>> {
+    let bad = |ln: usize, msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("line {ln}: {msg}"));
+    let mut table = HashMap::new();
+    let mut section = String::new();
+    for (i, raw) in src.lines().enumerate() {
+        let ln = i + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        if let Some((name, rest)) = line.strip_prefix('[').and_then(|l| l.split_once(']')) {
+            if !is_comment(rest) { return Err(bad(ln, "unexpected text after section")); }
+            section = name.trim().to_string();
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| bad(ln, "expected key = \"value\""))?;
+        let key = key.trim().trim_matches('"');
+        let (inner, rest) = split_quoted(value.trim()).ok_or_else(|| bad(ln, "value must be a quoted string"))?;
+        if !is_comment(rest) { return Err(bad(ln, "unexpected text after value")); }
+        let full = if section.is_empty() { key.to_string() } else { format!("{section}.{key}") };
+        table.insert(full, unescape(inner).ok_or_else(|| bad(ln, "bad escape"))?);
+    }
+    Ok(table)
+}
+
+/// `"..."` at the start of `s` -> (raw contents, text after the closing quote)
+fn split_quoted(s: &str) -> Option<(&str, &str)> {
+    let body = s.strip_prefix('"')?;
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some((&body[..i], &body[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Nothing but whitespace and an optional `# comment`
+fn is_comment(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' { out.push(c); continue; }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            '"' => out.push('"'),
+            '\\' => out.push('\\'),
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            _ => return None,
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,156 @@
+//! -------- Layers: named render layers composited by the engine --------
+//!
+//! Each layer is its own `PixelBuffer` (usually transparent) with visibility,
+//! blend mode, opacity and a scroll factor relative to the compositor's scroll
+//! position, so a 0.25 background moves slower than a 1.0 world layer and a 0.0
+//! UI layer stays put. Layers are composited bottom-to-top in insertion order.
+//! A layer given a `SpriteBatch` is drawn on the GPU instead (see `batch`).
+
+use crate::batch::SpriteBatch;
+use crate::camera::Camera;
+use crate::{BlendMode, PixelBuffer};
+use piston_window::G2dTexture;
+use std::cmp;
+
+pub struct Layer {
+    name: String,
+    buffer: PixelBuffer,
+    pub visible: bool,
+    pub blend: BlendMode,
+    /// 255 = as drawn, 0 = invisible
+    pub opacity: u8,
+    /// Multiplier applied to the compositor scroll (0.0 = fixed to screen)
+    pub scroll_factor: (f32, f32),
+    /// Tile the layer when scrolled past its edges (repeating backgrounds)
+    pub wrap: bool,
+    /// Color the layer is cleared to each frame; `None` keeps last frame's content
+    pub clear_color: Option<[u8; 4]>,
+    batch: Option<SpriteBatch>,
+}
+
+impl Layer {
+    #[inline] pub fn name(&self) -> &str { &self.name }
+    #[inline] pub fn buffer(&self) -> &PixelBuffer { &self.buffer }
+    #[inline] pub fn buffer_mut(&mut self) -> &mut PixelBuffer { &mut self.buffer }
+    /// Draw this layer as GPU quads from `texture` instead of compositing its
+    /// buffer; sprites are queued on `batch_mut`
+    pub fn use_gpu(&mut self, texture: G2dTexture) -> &mut SpriteBatch {
+        self.batch.insert(SpriteBatch::new(texture))
+    }
+    /// Back to CPU compositing of the buffer
+    pub fn use_cpu(&mut self) { self.batch = None; }
+    #[inline] pub fn is_gpu(&self) -> bool { self.batch.is_some() }
+    #[inline] pub fn batch(&self) -> Option<&SpriteBatch> { self.batch.as_ref() }
+    #[inline] pub fn batch_mut(&mut self) -> Option<&mut SpriteBatch> { self.batch.as_mut() }
+}
+
+/// Ordered set of layers plus the shared scroll position
+#[derive(Default)]
+pub struct Compositor {
+    layers: Vec<Layer>,
+    scroll: (f32, f32),
+}
+
+impl Compositor {
+    pub fn new() -> Self { Self::default() }
+    /// Append a transparent `w`x`h` layer on top; returns its index
+    pub fn add_layer(&mut self, name: &str, w: u32, h: u32) -> usize {
+        let mut buffer = PixelBuffer::new(w, h);
+        buffer.clear([0, 0, 0, 0]);
+        self.layers.push(Layer {
+            name: name.to_string(),
+            buffer,
+            visible: true,
+            blend: BlendMode::Alpha,
+            opacity: 255,
+            scroll_factor: (1.0, 1.0),
+            wrap: false,
+            clear_color: Some([0, 0, 0, 0]),
+            batch: None,
+        });
+        self.layers.len() - 1
+    }
+    pub fn remove_layer(&mut self, name: &str) -> bool {
+        let before = self.layers.len();
+        self.layers.retain(|l| l.name != name);
+        self.layers.len() != before
+    }
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+    /// Shorthand for drawing into a layer's buffer
+    pub fn buffer_mut(&mut self, name: &str) -> Option<&mut PixelBuffer> {
+        self.layer_mut(name).map(Layer::buffer_mut)
+    }
+    /// Shorthand for queuing sprites on a GPU layer
+    pub fn batch_mut(&mut self, name: &str) -> Option<&mut SpriteBatch> {
+        self.layer_mut(name).and_then(Layer::batch_mut)
+    }
+    pub(crate) fn layer_mut_at(&mut self, index: usize) -> &mut Layer { &mut self.layers[index] }
+    pub fn layers(&self) -> impl Iterator<Item = &Layer> { self.layers.iter() }
+    pub fn is_empty(&self) -> bool { self.layers.is_empty() }
+    /// Move a layer to a new position in the stack (0 = bottom)
+    pub fn move_layer(&mut self, name: &str, index: usize) -> bool {
+        let Some(from) = self.layers.iter().position(|l| l.name == name) else { return false };
+        let layer = self.layers.remove(from);
+        self.layers.insert(cmp::min(index, self.layers.len()), layer);
+        true
+    }
+    /// Camera-like scroll position shared by all layers (scaled per layer)
+    pub fn set_scroll(&mut self, x: f32, y: f32) { self.scroll = (x, y); }
+    #[inline] pub fn scroll(&self) -> (f32, f32) { self.scroll }
+    /// Scroll with the camera, so each layer's `scroll_factor` is its parallax factor
+    pub fn follow_camera(&mut self, cam: &Camera) { self.scroll = (cam.x, cam.y); }
+
+    /// Resize every layer that is exactly `old` in size to `new` (canvas changes)
+    pub fn resize_matching(&mut self, old: (u32, u32), new: (u32, u32)) {
+        for l in &mut self.layers {
+            if (l.buffer.width(), l.buffer.height()) == old {
+                l.buffer.resize(new.0, new.1);
+                l.buffer.clear(l.clear_color.unwrap_or([0, 0, 0, 0]));
+            }
+        }
+    }
+    /// Reset every layer to its clear color and empty GPU batches (engine calls
+    /// this before drawing); layers without a clear color keep both
+    pub fn clear(&mut self) {
+        for l in &mut self.layers {
+            let Some(c) = l.clear_color else { continue };
+            match &mut l.batch {
+                Some(b) => b.clear(),
+                None => l.buffer.clear(c),
+            }
+        }
+    }
+    /// Visible GPU layers with their scroll offset in layer pixels, bottom first
+    pub(crate) fn gpu_layers(&self) -> impl Iterator<Item = (&SpriteBatch, (f32, f32), u8)> {
+        self.layers.iter().filter(|l| l.visible && l.opacity > 0).filter_map(|l| {
+            let off = (self.scroll.0 * l.scroll_factor.0, self.scroll.1 * l.scroll_factor.1);
+            l.batch.as_ref().map(|b| (b, off, l.opacity))
+        })
+    }
+    /// Blend all visible CPU layers onto `target`, bottom first
+    pub fn composite(&self, target: &mut PixelBuffer) {
+        for l in self.layers.iter().filter(|l| l.visible && l.opacity > 0 && !l.is_gpu()) {
+            let ox = (self.scroll.0 * l.scroll_factor.0).floor() as i32;
+            let oy = (self.scroll.1 * l.scroll_factor.1).floor() as i32;
+            let (lw, lh) = (l.buffer.width() as i32, l.buffer.height() as i32);
+            if lw == 0 || lh == 0 { continue; }
+            for y in 0..target.height() as i32 {
+                let mut sy = y + oy;
+                if l.wrap { sy = sy.rem_euclid(lh); } else if sy < 0 || sy >= lh { continue; }
+                for x in 0..target.width() as i32 {
+                    let mut sx = x + ox;
+                    if l.wrap { sx = sx.rem_euclid(lw); } else if sx < 0 || sx >= lw { continue; }
+                    let s = l.buffer.px(sx as u32, sy as u32);
+                    if s[3] == 0 || !target.writable(x as u32, y as u32) { continue; }
+                    let d = target.px(x as u32, y as u32);
+                    target.set_px(x as u32, y as u32, l.blend.blend(s, d, l.opacity));
+                }
+            }
+        }
+    }
+}
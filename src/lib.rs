@@ -1,37 +1,97 @@
-use image::{ImageBuffer, Rgba};
-use std::collections::HashSet;
+use image::{ImageBuffer, Rgba, RgbaImage};
+use piston_window::texture::{CreateTexture, Format, UpdateTexture};
 use piston_window::{
-    clear, image::Image, Button, EventLoop, Filter, Flip, FocusEvent, G2dTexture, G2dTextureContext, Key, PistonWindow, PressEvent, ReleaseEvent, Texture, TextureSettings, UpdateEvent, Window, WindowSettings};
+    clear, image::Image, AdvancedWindow, Button, ControllerAxisEvent, EventLoop, Filter, Flip, FocusEvent, G2dTexture, G2dTextureContext, Key, PistonWindow, PressEvent, ReleaseEvent, RenderEvent, ResizeEvent, Texture, TextureSettings, Transformed, UpdateEvent, Window, WindowSettings};
 use std::cmp;
 
+pub mod accessibility;
+pub mod atlas;
+pub mod batch;
+pub mod behavior;
+pub mod camera;
+pub mod cli;
+pub mod context;
+pub mod crash;
+pub mod dialogue;
+pub mod display;
+pub mod error;
+pub mod fills;
+pub mod filters;
+pub mod gamepad;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+pub mod i18n;
+pub mod layers;
+pub mod lut;
+pub mod mask;
+pub mod math;
+pub mod nine_slice;
+pub mod palette;
+mod par;
+pub mod players;
+pub mod postfx;
+pub mod profile;
+pub mod scenes;
+pub mod scratch;
+pub mod shapes;
+mod simd;
+pub mod snapshot;
+pub mod stats;
+pub mod steering;
+pub mod timeline;
+pub mod viewport;
+
+use accessibility::ColorFilter;
+pub use error::{PixError, PixResult};
+use cli::EngineArgs;
+pub use display::{FullscreenMode, MonitorInfo, ScaleMode};
+pub use postfx::PostFx;
+pub use camera::{Camera, ParallaxLayer};
+pub use context::{Context, EngineCommand};
+use layers::Compositor;
+use mask::Mask;
+use scenes::{SceneChange, SceneManager};
+use scratch::Scratch;
+use viewport::{Viewport, ViewportId};
+
 /// -------- Engine constants (change to taste) --------
 const LOW_W: u32 = 320;
-const LOW_H: u32 = 180; // 16:9 pixel canvas
+const LOW_H: u32 = 180; // default 16:9 pixel canvas (see EngineConfig::canvas_size)
 const FIXED_DT: f64 = 1.0 / 60.0;
 
 
+/// `tex_ctx` is `None` in headless mode: images load, textures don't
 pub struct Assets<'a> {
-    tex_ctx: &'a mut G2dTextureContext,
+    tex_ctx: Option<&'a mut G2dTextureContext>,
 }
 
 impl<'a> Assets<'a> {
-    pub fn load_image(&mut self, path: &str) -> image::RgbaImage {
-        image::open(path).expect("img").to_rgba8()
+    pub fn load_image(&mut self, path: &str) -> PixResult<image::RgbaImage> {
+        Ok(image::open(path)?.to_rgba8())
     }
-    pub fn load_texture(&mut self, path: &str) -> G2dTexture {
+    pub fn load_texture(&mut self, path: &str) -> PixResult<G2dTexture> {
         Texture::from_path(
-            self.tex_ctx, path, Flip::None,
+            self.tex_ctx()?, path, Flip::None,
             &TextureSettings::new().filter(Filter::Nearest),
-        ).expect("tex")
+        ).map_err(|e| PixError::Texture(format!("{path}: {e}")))
+    }
+    #[inline] pub fn is_headless(&self) -> bool { self.tex_ctx.is_none() }
+    pub(crate) fn tex_ctx(&mut self) -> PixResult<&mut G2dTextureContext> {
+        self.tex_ctx.as_deref_mut().ok_or_else(|| PixError::Texture("no GPU in headless mode".into()))
     }
     // later: load_sound, load_font, etc.
 }
 
 /// -------- Render helpers --------
-pub fn make_nearest_texture(tc: &mut G2dTextureContext, buf: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> G2dTexture {
+pub fn make_nearest_texture(tc: &mut G2dTextureContext, buf: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> PixResult<G2dTexture> {
     let mut ts = TextureSettings::new();
     ts.set_filter(Filter::Nearest); // pixel crisp
-    Texture::from_image(tc, buf, &ts).expect("texture")
+    Texture::from_image(tc, buf, &ts).map_err(|e| PixError::Texture(format!("{e:?}")))
+}
+/// Nearest-filtered texture straight from a buffer's packed pixels
+fn buffer_texture(tc: &mut G2dTextureContext, fb: &PixelBuffer) -> PixResult<G2dTexture> {
+    let ts = TextureSettings::new().filter(Filter::Nearest);
+    Texture::create(tc, Format::Rgba8, fb.as_bytes(), [fb.w, fb.h], &ts).map_err(|e| PixError::Texture(format!("{e:?}")))
 }
 
 /// -------- PixelBuffer: your CPU-side framebuffer --------
@@ -39,29 +99,223 @@ pub fn make_nearest_texture(tc: &mut G2dTextureContext, buf: &ImageBuffer<Rgba<u
 pub struct PixelBuffer {
     w: u32,
     h: u32,
-    buf: ImageBuffer<Rgba<u8>, Vec<u8>>,
+    /// One native-endian `u32` per pixel, row-major, so the bytes are RGBA
+    /// in memory and rows are always word-aligned
+    buf: Vec<u32>,
+    mask: Option<Mask>,
+    blend_mode: BlendMode,
+    /// Effective clip rectangles, innermost last (each already intersected with its parent)
+    clips: Vec<Rect>,
 }
 impl crate::PixelBuffer {
     pub fn new(w: u32, h: u32) -> Self {
-        let buf = ImageBuffer::from_pixel(w, h, Rgba([0, 0, 0, 255]));
-        Self { w, h, buf }
+        let buf = vec![u32::from_ne_bytes([0, 0, 0, 255]); w as usize * h as usize];
+        Self { w, h, buf, mask: None, blend_mode: BlendMode::Alpha, clips: Vec::new() }
     }
     #[inline] pub fn width(&self) -> u32 { self.w }
     #[inline] pub fn height(&self) -> u32 { self.h }
+    /// Copy of `img` (no mask or clip, alpha blending)
+    pub fn from_image(img: &RgbaImage) -> Self {
+        let mut fb = Self::new(img.width(), img.height());
+        fb.as_bytes_mut().copy_from_slice(img.as_raw());
+        fb
+    }
+    /// Copy out as an `image` buffer (saving, comparisons)
+    pub fn to_image(&self) -> RgbaImage {
+        RgbaImage::from_raw(self.w, self.h, self.as_bytes().to_vec()).expect("buffer holds w*h pixels")
+    }
+    #[inline]
+    fn index(&self, x: u32, y: u32) -> usize { y as usize * self.w as usize + x as usize }
+    /// In-bounds pixel read without the `Option`
+    #[inline]
+    pub(crate) fn px(&self, x: u32, y: u32) -> [u8; 4] { self.buf[self.index(x, y)].to_ne_bytes() }
+    /// In-bounds raw pixel write (no clip, mask or blend mode)
+    #[inline]
+    pub(crate) fn set_px(&mut self, x: u32, y: u32, c: [u8; 4]) {
+        let i = self.index(x, y);
+        self.buf[i] = u32::from_ne_bytes(c);
+    }
+    /// Reallocate at a new size (contents reset to opaque black, mask and clip
+    /// stack dropped, blend mode kept).
+    /// Resizing the engine's framebuffer requests that canvas resolution, as
+    /// `PixEngine::set_canvas_size` would (the engine re-pads it for smooth
+    /// scrolling, so the contents are reset again in that mode).
+    pub fn resize(&mut self, w: u32, h: u32) {
+        if w == self.w && h == self.h { return; }
+        let mode = self.blend_mode;
+        *self = PixelBuffer::new(w, h);
+        self.blend_mode = mode;
+    }
+    /// Restrict all drawing to pixels whose mask bit is set (`None` = unrestricted).
+    /// `clear` ignores the mask.
+    pub fn set_mask(&mut self, mask: Option<Mask>) { self.mask = mask; }
+    #[inline] pub fn mask(&self) -> Option<&Mask> { self.mask.as_ref() }
+    #[inline] pub fn mask_mut(&mut self) -> Option<&mut Mask> { self.mask.as_mut() }
+    /// Take the mask back out, leaving drawing unrestricted
+    pub fn take_mask(&mut self) -> Option<Mask> { self.mask.take() }
+    /// How `put`, lines, shapes, fills and blits combine with the buffer.
+    /// `Alpha` (default): shapes overwrite, blits alpha-over. Other modes apply
+    /// to everything, with the source alpha as coverage. `clear` and
+    /// `flood_fill` always overwrite.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) { self.blend_mode = mode; }
+    #[inline] pub fn blend_mode(&self) -> BlendMode { self.blend_mode }
+    /// Confine all drawing (except `clear`) to `r`, intersected with the current
+    /// clip. Nest freely; undo with `pop_clip`.
+    pub fn push_clip(&mut self, r: Rect) {
+        let inner = match self.clips.last() {
+            Some(outer) => outer.intersect(&r).unwrap_or_default(),
+            None => r,
+        };
+        self.clips.push(inner);
+    }
+    /// Restore the previous clip; returns the removed (effective) rectangle
+    pub fn pop_clip(&mut self) -> Option<Rect> { self.clips.pop() }
+    /// Current effective clip, `None` = whole buffer
+    #[inline] pub fn clip_rect(&self) -> Option<Rect> { self.clips.last().copied() }
+    /// Writable area: the buffer, narrowed by the clip. Intersect with it once
+    /// to pre-clip loops that then use `put_unchecked` / `span_unchecked`.
+    #[inline]
+    pub fn bounds(&self) -> Rect {
+        let all = Rect::new(0, 0, self.w, self.h);
+        self.clips.last().map_or(all, |c| c.intersect(&all).unwrap_or_default())
+    }
+    /// True when plain writes need no per-pixel checks beyond `bounds`
+    /// (no mask, alpha blending), so row kernels can be used
+    #[inline]
+    fn direct(&self) -> bool { self.mask.is_none() && self.blend_mode == BlendMode::Alpha }
+    #[inline]
+    fn writable(&self, x: u32, y: u32) -> bool {
+        self.clips.last().is_none_or(|c| c.contains(x as i32, y as i32))
+            && self.mask.as_ref().is_none_or(|m| m.get(x as i32, y as i32))
+    }
     /// Clear to RGBA
     pub fn clear(&mut self, color: [u8; 4]) {
-        // Fast bulk clear: fill + fix alpha if needed
-        if color == [0, 0, 0, 255] {
-            self.buf.as_mut().fill(0);
-            for p in self.buf.pixels_mut() { p.0[3] = 255; }
-        } else {
-            for p in self.buf.pixels_mut() { *p = Rgba(color); }
+        self.for_each_row(0..self.h as usize, |_, row| simd::fill_row(row, color));
+    }
+    /// Raw RGBA8 bytes, row-major, `width * height * 4` long
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: u32 storage reinterpreted as its bytes: same memory, 4 per pixel
+        unsafe { std::slice::from_raw_parts(self.buf.as_ptr().cast(), self.buf.len() * 4) }
+    }
+    /// Mutable raw RGBA8 bytes. Writes here bypass the clip, mask and blend mode.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as `as_bytes`; every byte pattern is a valid u32
+        unsafe { std::slice::from_raw_parts_mut(self.buf.as_mut_ptr().cast(), self.buf.len() * 4) }
+    }
+    /// The buffer as one native-endian `u32` per pixel (`0xAABBGGRR` on
+    /// little-endian targets)
+    #[inline] pub fn as_u32_slice(&self) -> &[u32] { &self.buf }
+    /// Mutable packed pixels. Writes bypass the clip, mask and blend mode.
+    #[inline] pub fn as_u32_slice_mut(&mut self) -> &mut [u32] { &mut self.buf }
+    /// Scanlines top to bottom, one `width`-long pixel slice each
+    pub fn rows(&self) -> impl Iterator<Item = &[[u8; 4]]> {
+        self.as_bytes().as_chunks::<4>().0.chunks_exact(self.w.max(1) as usize)
+    }
+    /// Mutable scanlines top to bottom. Writes bypass the clip, mask and blend mode.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [[u8; 4]]> {
+        let w = self.w.max(1) as usize;
+        self.as_bytes_mut().as_chunks_mut::<4>().0.chunks_exact_mut(w)
+    }
+    /// Mutable per-scanline slices of `r`, clipped to the buffer
+    pub fn region_mut(&mut self, r: Rect) -> impl Iterator<Item = &mut [[u8; 4]]> {
+        let r = r.intersect(&Rect::new(0, 0, self.w, self.h)).unwrap_or_default();
+        let (x0, x1) = (r.x as usize, r.right() as usize);
+        self.rows_mut().skip(r.y as usize).take(r.h as usize).map(move |row| &mut row[x0..x1])
+    }
+    /// Bounding box of the pixels that differ from `prev`: the whole buffer if
+    /// the sizes differ, `None` if nothing changed. Row compares are memcmp-fast.
+    pub fn diff_rect(&self, prev: &PixelBuffer) -> Option<Rect> {
+        if (self.w, self.h) != (prev.w, prev.h) {
+            return (self.w > 0 && self.h > 0).then_some(Rect::new(0, 0, self.w, self.h));
+        }
+        self.diff_pixels(&prev.buf)
+    }
+    /// `diff_rect` against the raw pixels of a same-sized buffer; any other
+    /// length counts as everything changed
+    pub(crate) fn diff_pixels(&self, prev: &[u32]) -> Option<Rect> {
+        if prev.len() != self.buf.len() {
+            return (self.w > 0 && self.h > 0).then_some(Rect::new(0, 0, self.w, self.h));
+        }
+        let w = self.w as usize;
+        if w == 0 { return None; }
+        let (mut x0, mut x1, mut y0, mut y1) = (usize::MAX, 0, usize::MAX, 0);
+        for (y, (a, b)) in self.buf.chunks_exact(w).zip(prev.chunks_exact(w)).enumerate() {
+            if a == b { continue; }
+            let first = a.iter().zip(b).position(|(p, q)| p != q).unwrap_or(0);
+            let last = a.iter().zip(b).rposition(|(p, q)| p != q).unwrap_or(0);
+            (x0, x1, y0, y1) = (x0.min(first), x1.max(last), y0.min(y), y);
         }
+        (y0 != usize::MAX).then(|| Rect::new(x0 as i32, y0 as i32, (x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32))
+    }
+    /// Pixel at (x, y), or `None` when out of bounds
+    #[inline]
+    pub fn get(&self, x: i32, y: i32) -> Option<[u8; 4]> {
+        if x < 0 || y < 0 || x as u32 >= self.w || y as u32 >= self.h { return None; }
+        Some(self.px(x as u32, y as u32))
+    }
+    /// Pixel at (x, y) without bounds checks.
+    ///
+    /// # Safety
+    /// `x < width()` and `y < height()` must hold.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, x: u32, y: u32) -> [u8; 4] {
+        self.buf.get_unchecked(self.index(x, y)).to_ne_bytes()
+    }
+    /// Raw write of `c` at (x, y): no bounds check, and the clip, mask and
+    /// blend mode are bypassed. For inner loops that clip up front.
+    ///
+    /// # Safety
+    /// `x < width()` and `y < height()` must hold.
+    #[inline]
+    pub unsafe fn put_unchecked(&mut self, x: u32, y: u32, c: [u8; 4]) {
+        debug_assert!(x < self.w && y < self.h, "put_unchecked({x}, {y}) outside {}x{}", self.w, self.h);
+        let i = self.index(x, y);
+        *self.buf.get_unchecked_mut(i) = u32::from_ne_bytes(c);
+    }
+    /// Raw fill of `len` pixels from (x, y) rightwards, like `put_unchecked`
+    ///
+    /// # Safety
+    /// `y < height()` and `x + len <= width()` must hold.
+    #[inline]
+    pub unsafe fn span_unchecked(&mut self, x: u32, y: u32, len: u32, c: [u8; 4]) {
+        debug_assert!(y < self.h && x as u64 + len as u64 <= self.w as u64, "span_unchecked outside the buffer");
+        let i = self.index(x, y);
+        self.buf.get_unchecked_mut(i..i + len as usize).fill(u32::from_ne_bytes(c));
+    }
+    /// Pixel at (x, y) with coordinates clamped to the nearest edge
+    /// (black for an empty buffer)
+    pub fn get_clamped(&self, x: i32, y: i32) -> [u8; 4] {
+        if self.w == 0 || self.h == 0 { return [0, 0, 0, 255]; }
+        let x = x.clamp(0, self.w as i32 - 1) as u32;
+        let y = y.clamp(0, self.h as i32 - 1) as u32;
+        self.px(x, y)
+    }
+    /// Pixel at (x, y) with coordinates wrapped around the edges (tiling)
+    pub fn get_wrapped(&self, x: i32, y: i32) -> [u8; 4] {
+        if self.w == 0 || self.h == 0 { return [0, 0, 0, 255]; }
+        let x = x.rem_euclid(self.w as i32) as u32;
+        let y = y.rem_euclid(self.h as i32) as u32;
+        self.px(x, y)
+    }
+    /// Bilinear sample at a fractional position (pixel centres at +0.5), edges clamped
+    pub fn sample_bilinear(&self, x: f32, y: f32) -> [u8; 4] {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let (a, b) = (self.get_clamped(x0, y0), self.get_clamped(x0 + 1, y0));
+        let (c, d) = (self.get_clamped(x0, y0 + 1), self.get_clamped(x0 + 1, y0 + 1));
+        std::array::from_fn(|i| {
+            let top = a[i] as f32 + (b[i] as f32 - a[i] as f32) * fx;
+            let bot = c[i] as f32 + (d[i] as f32 - c[i] as f32) * fx;
+            (top + (bot - top) * fy).round() as u8
+        })
     }
     /// Safe pixel plot (clamped)
     pub fn put(&mut self, x: i32, y: i32, c: [u8; 4]) {
         if x >= 0 && y >= 0 && (x as u32) < self.w && (y as u32) < self.h {
-            self.buf.put_pixel(x as u32, y as u32, Rgba(c));
+            self.write(x as u32, y as u32, c);
         }
     }
     /// Bresenham line
@@ -88,141 +342,1810 @@ impl crate::PixelBuffer {
         sprite_h: u32,
         pixels: &[[u8; 4]],
     ) {
-        let sw = sprite_w as i32;
-        let sh = sprite_h as i32;
-        for j in 0..sh {
-            for i in 0..sw {
-                let px = i + sx;
-                let py = j + sy;
-                if px < 0 || py < 0 || (px as u32) >= self.w || (py as u32) >= self.h { continue; }
-                let s = pixels[(j as usize) * sprite_w as usize + i as usize];
-                let a = s[3] as f32 / 255.0;
-                if a <= 0.0 { continue; }
-                let dst = self.buf.get_pixel(px as u32, py as u32).0;
-                let out = [
-                    (s[0] as f32 * a + dst[0] as f32 * (1.0 - a)) as u8,
-                    (s[1] as f32 * a + dst[1] as f32 * (1.0 - a)) as u8,
-                    (s[2] as f32 * a + dst[2] as f32 * (1.0 - a)) as u8,
-                    255,
-                ];
-                self.buf.put_pixel(px as u32, py as u32, Rgba(out));
+        // clip once; the loops below only visit visible pixels
+        let Some(r) = Rect::new(sx, sy, sprite_w, sprite_h).intersect(&self.bounds()) else { return };
+        let (x0, n) = ((r.x - sx) as usize, r.w as usize);
+        let direct = self.direct();
+        for y in r.y..r.bottom() {
+            let src = &pixels[(y - sy) as usize * sprite_w as usize + x0..][..n];
+            if !direct {
+                for (x, &s) in (r.x as u32..).zip(src) { self.blend(x, y as u32, s); }
+                continue;
+            }
+            let d0 = (y as usize * self.w as usize + r.x as usize) * 4;
+            let dst = &mut self.as_bytes_mut()[d0..d0 + n * 4];
+            // fully opaque rows (most tiles and sprite middles) are a plain copy
+            if src.iter().all(|p| p[3] == 255) {
+                dst.copy_from_slice(src.as_flattened());
+            } else {
+                simd::blend_row(dst, src);
+            }
+        }
+    }
+    /// Copy a sprite known to be opaque (tiles, backgrounds) without blending;
+    /// rows are copied whole when the buffer has no mask or blend mode
+    pub fn blit_rgba_opaque(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+    ) {
+        let Some(r) = Rect::new(sx, sy, sprite_w, sprite_h).intersect(&self.bounds()) else { return };
+        let (x0, n) = ((r.x - sx) as usize, r.w as usize);
+        for y in r.y..r.bottom() {
+            let src = &pixels[(y - sy) as usize * sprite_w as usize + x0..][..n];
+            if self.direct() {
+                let d0 = (y as usize * self.w as usize + r.x as usize) * 4;
+                self.as_bytes_mut()[d0..d0 + n * 4].copy_from_slice(src.as_flattened());
+            } else {
+                for (x, &c) in (r.x..).zip(src) { self.write(x as u32, y as u32, c); }
+            }
+        }
+    }
+    /// `blit_rgba` with per-blit effects (see `BlitFx`)
+    pub fn blit_rgba_fx(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        fx: BlitFx,
+    ) {
+        let Some(r) = Rect::new(sx, sy, sprite_w, sprite_h).intersect(&self.bounds()) else { return };
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                let s = pixels[(y - sy) as usize * sprite_w as usize + (x - sx) as usize];
+                self.blend(x as u32, y as u32, fx.apply(s));
             }
         }
     }
+    /// `blit_rgba` with the sprite's alpha scaled by `opacity`
+    pub fn blit_rgba_opacity(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        opacity: u8,
+    ) {
+        self.blit_rgba_fx(sx, sy, sprite_w, sprite_h, pixels, BlitFx::opacity(opacity));
+    }
+    /// `blit_rgba` that treats pixels of the `key` color as transparent
+    pub fn blit_rgba_keyed(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        key: [u8; 3],
+    ) {
+        self.blit_rgba_fx(sx, sy, sprite_w, sprite_h, pixels, BlitFx::color_key(key));
+    }
+    /// Solid silhouette: every sprite pixel drawn in `c`, keeping its coverage
+    /// (hit flashes, shadows, "behind a wall" ghosts)
+    pub fn blit_silhouette(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        c: [u8; 4],
+    ) {
+        let Some(r) = Rect::new(sx, sy, sprite_w, sprite_h).intersect(&self.bounds()) else { return };
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                let a = pixels[(y - sy) as usize * sprite_w as usize + (x - sx) as usize][3];
+                self.blend(x as u32, y as u32, [c[0], c[1], c[2], (c[3] as u32 * a as u32 / 255) as u8]);
+            }
+        }
+    }
+    /// `blit_rgba` at `pos` with a drop shadow: the sprite's silhouette in
+    /// `shadow`, offset by `offset`, is drawn underneath first
+    pub fn blit_with_shadow(
+        &mut self,
+        pos: (i32, i32),
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        offset: (i32, i32),
+        shadow: [u8; 4],
+    ) {
+        let (x, y) = pos;
+        self.blit_silhouette(x + offset.0, y + offset.1, sprite_w, sprite_h, pixels, shadow);
+        self.blit_rgba(x, y, sprite_w, sprite_h, pixels);
+    }
+    /// 1px outline in `c` around the sprite's opaque (alpha >= 128) pixels:
+    /// transparent pixels, including a 1px ring outside the sprite, that touch
+    /// an opaque one edge-on. Draw it before or after the sprite itself.
+    pub fn blit_outline(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        c: [u8; 4],
+    ) {
+        let (sw, sh) = (sprite_w as i32, sprite_h as i32);
+        let solid = |x: i32, y: i32| {
+            x >= 0 && y >= 0 && x < sw && y < sh && pixels[(y * sw + x) as usize][3] >= 128
+        };
+        let ring = Rect::new(sx - 1, sy - 1, sprite_w + 2, sprite_h + 2);
+        let Some(r) = ring.intersect(&self.bounds()) else { return };
+        for y in r.y..r.bottom() {
+            for x in r.x..r.right() {
+                let (u, v) = (x - sx, y - sy);
+                if !solid(u, v) && (solid(u - 1, v) || solid(u + 1, v) || solid(u, v - 1) || solid(u, v + 1)) {
+                    self.blend(x as u32, y as u32, c);
+                }
+            }
+        }
+    }
+    /// Nearest-neighbour scaled alpha-blit; the sprite covers
+    /// `round(sprite_w * scale.0)` x `round(sprite_h * scale.1)` pixels from (sx, sy).
+    /// Columns are stepped with an exact integer DDA, so every source pixel
+    /// maps to an even run of destination pixels.
+    pub fn blit_rgba_scaled(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        scale: (f32, f32),
+    ) {
+        let dw = (sprite_w as f32 * scale.0).round() as i64;
+        let dh = (sprite_h as f32 * scale.1).round() as i64;
+        if dw <= 0 || dh <= 0 { return; }
+        let (sw, sh) = (sprite_w as i64, sprite_h as i64);
+        let b = self.bounds();
+        let x0 = cmp::max(sx as i64, b.x as i64);
+        let x1 = cmp::min(sx as i64 + dw, b.right() as i64);
+        let y0 = cmp::max(sy as i64, b.y as i64);
+        let y1 = cmp::min(sy as i64 + dh, b.bottom() as i64);
+        // source column = (x - sx) * sw / dw, advanced by quotient + remainder
+        let (q, r) = (sw / dw, sw % dw);
+        for y in y0..y1 {
+            let v = ((y - sy as i64) * sh / dh) as usize;
+            let row = &pixels[v * sprite_w as usize..(v + 1) * sprite_w as usize];
+            let n = (x0 - sx as i64) * sw;
+            let (mut u, mut e) = (n / dw, n % dw);
+            for x in x0..x1 {
+                self.blend(x as u32, y as u32, row[u as usize]);
+                u += q;
+                e += r;
+                if e >= dw { e -= dw; u += 1; }
+            }
+        }
+    }
+    /// Rotate + scale blit via inverse mapping. Each destination pixel is mapped
+    /// back into sprite space, so there are no holes at any angle or zoom.
+    /// `(dx, dy)` is where the sprite's `rz.pivot` lands on the buffer.
+    pub fn blit_rgba_rotozoom(
+        &mut self,
+        dx: f32,
+        dy: f32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        rz: RotoZoom,
+    ) {
+        if sprite_w == 0 || sprite_h == 0 || rz.scale <= 0.0 { return; }
+        let (sin, cos) = rz.angle.sin_cos();
+        let inv = 1.0 / rz.scale;
+        // sprite-space step per destination pixel along x and y
+        let (ux, vx) = (cos * inv, -sin * inv);
+        let (uy, vy) = (sin * inv, cos * inv);
+
+        // destination region: whole buffer when tiling, else the rotated bounds
+        let (x0, y0, x1, y1) = if rz.wrap {
+            (0, 0, self.w as i32, self.h as i32)
+        } else {
+            let (px, py) = rz.pivot;
+            let corners = [(-px, -py), (sprite_w as f32 - px, -py),
+                           (-px, sprite_h as f32 - py), (sprite_w as f32 - px, sprite_h as f32 - py)];
+            let (mut minx, mut miny, mut maxx, mut maxy) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+            for (cx, cy) in corners {
+                let x = (cx * cos - cy * sin) * rz.scale + dx;
+                let y = (cx * sin + cy * cos) * rz.scale + dy;
+                minx = minx.min(x); maxx = maxx.max(x);
+                miny = miny.min(y); maxy = maxy.max(y);
+            }
+            (cmp::max(minx.floor() as i32, 0), cmp::max(miny.floor() as i32, 0),
+             cmp::min(maxx.ceil() as i32, self.w as i32), cmp::min(maxy.ceil() as i32, self.h as i32))
+        };
+        if x0 >= x1 || y0 >= y1 { return; }
+
+        let chunk = rz.chunk.max(1) as i32;
+        let (sw, sh) = (sprite_w as i32, sprite_h as i32);
+        let mut y = y0;
+        while y < y1 {
+            let bh = cmp::min(chunk, y1 - y);
+            // sample at the centre of the block (or pixel when chunk == 1)
+            let fy = y as f32 + bh as f32 * 0.5 - dy;
+            let fx = x0 as f32 + cmp::min(chunk, x1 - x0) as f32 * 0.5 - dx;
+            let mut u = fx * ux + fy * uy + rz.pivot.0;
+            let mut v = fx * vx + fy * vy + rz.pivot.1;
+            let mut x = x0;
+            while x < x1 {
+                let bw = cmp::min(chunk, x1 - x);
+                let (mut iu, mut iv) = (u.floor() as i32, v.floor() as i32);
+                let inside = if rz.wrap {
+                    iu = iu.rem_euclid(sw);
+                    iv = iv.rem_euclid(sh);
+                    true
+                } else {
+                    iu >= 0 && iv >= 0 && iu < sw && iv < sh
+                };
+                if inside {
+                    let s = pixels[iv as usize * sprite_w as usize + iu as usize];
+                    for by in y..y + bh {
+                        for bx in x..x + bw {
+                            self.blend(bx as u32, by as u32, s);
+                        }
+                    }
+                }
+                u += ux * chunk as f32;
+                v += vx * chunk as f32;
+                x += chunk;
+            }
+            y += chunk;
+        }
+    }
+    /// Rotate a sprite by `angle` radians (clockwise) about `pivot`, a point in
+    /// sprite space that lands at `pos`. Nearest sampling, no holes.
+    pub fn blit_rgba_rotated(
+        &mut self,
+        pos: (f32, f32),
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        angle: f32,
+        pivot: (f32, f32),
+    ) {
+        let rz = RotoZoom { angle, pivot, ..RotoZoom::default() };
+        self.blit_rgba_rotozoom(pos.0, pos.1, sprite_w, sprite_h, pixels, rz);
+    }
+    /// RotSprite-quality `blit_rgba_rotated`: the sprite is upscaled 8x with
+    /// Scale2x before rotating, which keeps thin lines and diagonals clean.
+    /// Costs a 64x temporary per call; pre-render fixed angles if it shows up.
+    pub fn blit_rgba_rotsprite(
+        &mut self,
+        pos: (f32, f32),
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        angle: f32,
+        pivot: (f32, f32),
+    ) {
+        let (mut w, mut h, mut up) = (sprite_w, sprite_h, pixels.to_vec());
+        for _ in 0..3 {
+            up = scale2x(w, h, &up);
+            (w, h) = (w * 2, h * 2);
+        }
+        let rz = RotoZoom { angle, scale: 0.125, pivot: (pivot.0 * 8.0, pivot.1 * 8.0), ..RotoZoom::default() };
+        self.blit_rgba_rotozoom(pos.0, pos.1, w, h, &up, rz);
+    }
+    /// Alpha-blit the `src_rect` region of another buffer with its top-left at
+    /// (dx, dy), clipped to both buffers (blend mode, clip and mask applied)
+    pub fn blit_buffer(&mut self, src: &PixelBuffer, src_rect: Rect, dx: i32, dy: i32) {
+        let Some((r, dx, dy)) = self.clip_copy(src, src_rect, dx, dy) else { return };
+        for y in 0..r.h as i32 {
+            for x in 0..r.w as i32 {
+                let s = src.px((r.x + x) as u32, (r.y + y) as u32);
+                self.blend((dx + x) as u32, (dy + y) as u32, s);
+            }
+        }
+    }
+    /// Opaque copy of the `src_rect` region of another buffer with its top-left
+    /// at (dx, dy), clipped to both buffers (clip and mask applied)
+    pub fn copy_buffer(&mut self, src: &PixelBuffer, src_rect: Rect, dx: i32, dy: i32) {
+        let Some((r, dx, dy)) = self.clip_copy(src, src_rect, dx, dy) else { return };
+        let n = r.w as usize;
+        for y in 0..r.h as i32 {
+            let s0 = src.index(r.x as u32, (r.y + y) as u32);
+            let d0 = self.index(dx as u32, (dy + y) as u32);
+            if self.mask.is_none() {
+                self.buf[d0..d0 + n].copy_from_slice(&src.buf[s0..s0 + n]);
+                continue;
+            }
+            for x in 0..r.w as i32 {
+                if self.writable((dx + x) as u32, (dy + y) as u32) {
+                    self.buf[d0 + x as usize] = src.buf[s0 + x as usize];
+                }
+            }
+        }
+    }
+    /// Opaque copy of all of `src` with its top-left at (dx, dy), clipped
+    pub(crate) fn copy_from(&mut self, src: &PixelBuffer, dx: i32, dy: i32) {
+        self.copy_buffer(src, Rect::new(0, 0, src.w, src.h), dx, dy);
+    }
+    /// Clip a source region against `src` and this buffer's `bounds`; returns the visible source
+    /// rect and where its top-left lands
+    fn clip_copy(&self, src: &PixelBuffer, src_rect: Rect, dx: i32, dy: i32) -> Option<(Rect, i32, i32)> {
+        let r = src_rect.intersect(&Rect::new(0, 0, src.w, src.h))?;
+        let (dx, dy) = (dx + r.x - src_rect.x, dy + r.y - src_rect.y);
+        let d = Rect::new(dx, dy, r.w, r.h).intersect(&self.bounds())?;
+        Some((Rect::new(r.x + d.x - dx, r.y + d.y - dy, d.w, d.h), d.x, d.y))
+    }
+    /// Opaque shape write of an in-bounds pixel (mask and blend mode applied)
+    #[inline]
+    fn write(&mut self, x: u32, y: u32, c: [u8; 4]) {
+        if !self.writable(x, y) { return; }
+        let c = if self.blend_mode == BlendMode::Alpha { c } else { self.blend_mode.blend(c, self.px(x, y), 255) };
+        self.set_px(x, y, c);
+    }
+    /// Simple alpha-over of one source pixel onto an in-bounds destination pixel
+    /// (or the buffer's blend mode, if set). 8-bit fixed point throughout.
+    #[inline]
+    fn blend(&mut self, x: u32, y: u32, s: [u8; 4]) {
+        let a = s[3] as u32;
+        if a == 0 || !self.writable(x, y) { return; }
+        let i = self.index(x, y);
+        let mut d = self.buf[i].to_ne_bytes();
+        if self.blend_mode != BlendMode::Alpha {
+            d = self.blend_mode.blend(s, d, 255);
+        } else {
+            simd::blend_px(&mut d, s);
+        }
+        self.buf[i] = u32::from_ne_bytes(d);
+    }
+}
+
+/// `v / 255` rounded, for `v <= 255 * 255`, without a division
+#[inline]
+fn div255(v: u32) -> u8 {
+    let v = v + 128;
+    ((v + (v >> 8)) >> 8) as u8
+}
+
+/// Integer rectangle (top-left + size) in pixel coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Rect {
+    #[inline] pub const fn new(x: i32, y: i32, w: u32, h: u32) -> Self { Self { x, y, w, h } }
+    #[inline] pub fn right(&self) -> i32 { self.x + self.w as i32 }
+    #[inline] pub fn bottom(&self) -> i32 { self.y + self.h as i32 }
+    #[inline] pub fn is_empty(&self) -> bool { self.w == 0 || self.h == 0 }
+    #[inline] pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.right() && y < self.bottom()
+    }
+    /// Overlapping area, or `None` if the rectangles don't touch
+    pub fn intersect(&self, o: &Rect) -> Option<Rect> {
+        let x0 = cmp::max(self.x, o.x);
+        let y0 = cmp::max(self.y, o.y);
+        let x1 = cmp::min(self.right(), o.right());
+        let y1 = cmp::min(self.bottom(), o.bottom());
+        (x0 < x1 && y0 < y1).then(|| Rect::new(x0, y0, (x1 - x0) as u32, (y1 - y0) as u32))
+    }
+}
+
+/// How a source color combines with what is already in the buffer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Plain alpha-over
+    #[default]
+    Alpha,
+    /// Lighten: glows, fire, lasers
+    Add,
+    /// Darken: shadows, tints
+    Multiply,
+    /// Soft lighten
+    Screen,
+    /// Darken by subtraction
+    Subtract,
+}
+
+impl BlendMode {
+    /// Combine `src` onto `dst`. Source alpha (scaled by `opacity`) is the coverage;
+    /// the result is always opaque.
+    #[inline]
+    pub fn blend(self, src: [u8; 4], dst: [u8; 4], opacity: u8) -> [u8; 4] {
+        let a = src[3] as u32 * opacity as u32 / 255;
+        let mut out = [0, 0, 0, 255];
+        for i in 0..3 {
+            let (s, d) = (src[i] as u32, dst[i] as u32);
+            let mixed = match self {
+                BlendMode::Alpha => s,
+                BlendMode::Add => cmp::min(s + d, 255),
+                BlendMode::Multiply => s * d / 255,
+                BlendMode::Screen => 255 - (255 - s) * (255 - d) / 255,
+                BlendMode::Subtract => d.saturating_sub(s),
+            };
+            out[i] = ((mixed * a + d * (255 - a)) / 255) as u8;
+        }
+        out
+    }
+}
+
+/// Per-blit effects for `PixelBuffer::blit_rgba_fx`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlitFx {
+    /// Multiplied into every source pixel, alpha included (damage flashes,
+    /// team colors, night tints)
+    pub tint: Option<[u8; 4]>,
+    /// Source pixels with exactly this RGB are skipped, whatever their alpha
+    /// (legacy sheets keyed on magenta `[255, 0, 255]`)
+    pub color_key: Option<[u8; 3]>,
+    /// Scales the sprite's alpha: 255 = as drawn, 0 = invisible (fades, ghosts)
+    pub opacity: u8,
+}
+impl Default for BlitFx {
+    fn default() -> Self { Self { tint: None, color_key: None, opacity: 255 } }
+}
+impl BlitFx {
+    #[inline] pub fn tint(c: [u8; 4]) -> Self { Self { tint: Some(c), ..Self::default() } }
+    #[inline] pub fn color_key(k: [u8; 3]) -> Self { Self { color_key: Some(k), ..Self::default() } }
+    #[inline] pub fn opacity(a: u8) -> Self { Self { opacity: a, ..Self::default() } }
+    /// Source pixel after the effects, ready to blend
+    #[inline]
+    pub fn apply(&self, mut s: [u8; 4]) -> [u8; 4] {
+        if self.color_key.is_some_and(|k| k == [s[0], s[1], s[2]]) { return [0; 4]; }
+        if let Some(t) = self.tint {
+            for i in 0..4 { s[i] = (s[i] as u32 * t[i] as u32 / 255) as u8; }
+        }
+        s[3] = (s[3] as u32 * self.opacity as u32 / 255) as u8;
+        s
+    }
+}
+
+/// Scale2x (EPX) pixel-art upscale: doubles both dimensions, rounding
+/// staircase corners instead of just duplicating pixels
+pub fn scale2x(w: u32, h: u32, pixels: &[[u8; 4]]) -> Vec<[u8; 4]> {
+    let (w, h) = (w as usize, h as usize);
+    let at = |x: usize, y: usize| pixels[y * w + x];
+    let mut out = vec![[0; 4]; w * h * 4];
+    for y in 0..h {
+        for x in 0..w {
+            let p = at(x, y);
+            let a = at(x, y.saturating_sub(1));
+            let b = at(cmp::min(x + 1, w - 1), y);
+            let c = at(x.saturating_sub(1), y);
+            let d = at(x, cmp::min(y + 1, h - 1));
+            let (o0, o1) = (2 * y * 2 * w + 2 * x, (2 * y + 1) * 2 * w + 2 * x);
+            out[o0] = if c == a && c != d && a != b { a } else { p };
+            out[o0 + 1] = if a == b && a != c && b != d { b } else { p };
+            out[o1] = if d == c && d != b && c != a { c } else { p };
+            out[o1 + 1] = if b == d && b != a && d != c { d } else { p };
+        }
+    }
+    out
+}
+
+/// Parameters for `PixelBuffer::blit_rgba_rotozoom`
+#[derive(Clone, Copy, Debug)]
+pub struct RotoZoom {
+    /// Rotation in radians (clockwise on screen, since y points down)
+    pub angle: f32,
+    /// Zoom factor; 1.0 = one sprite pixel per buffer pixel
+    pub scale: f32,
+    /// Sprite-space point that is placed at the destination position
+    pub pivot: (f32, f32),
+    /// Tile the source infinitely and cover the whole buffer (rotozoom backgrounds)
+    pub wrap: bool,
+    /// Sample once per `chunk`x`chunk` block instead of per pixel (1 = off)
+    pub chunk: u32,
+}
+impl Default for RotoZoom {
+    fn default() -> Self {
+        Self { angle: 0.0, scale: 1.0, pivot: (0.0, 0.0), wrap: false, chunk: 1 }
+    }
 }
 
 /// -------- Scene trait: plug in your game/effect --------
 pub trait Scene {
-    fn update(&mut self, dt: f64, fb: &mut crate::PixelBuffer);
-    fn draw(&self, fb: &mut crate::PixelBuffer);
+    /// One fixed step; `ctx.dt()` is the step length
+    fn update(&mut self, ctx: &mut Context, fb: &mut crate::PixelBuffer);
+    fn draw(&self, ctx: &Context, fb: &mut crate::PixelBuffer);
     fn key_event(&mut self, _key: Key, _down: bool) { } // optional
+    /// Raw gamepad button (`id` = controller, `button` = backend button number)
+    fn gamepad_button(&mut self, _id: u32, _button: u8, _down: bool) {} // optional
+    /// Raw gamepad axis, position usually -1.0..=1.0
+    fn gamepad_axis(&mut self, _id: u32, _axis: u8, _position: f64) {} // optional
     fn on_load(&mut self, _assets: &mut Assets) {} // once
+    /// Draw into the engine's layers (only called when layers were added)
+    fn draw_layers(&self, _layers: &mut Compositor) {} // optional
+    /// Fractional camera remainder in 0.0..1.0, applied at upscale time when
+    /// smooth scrolling is on. Draw the world at the floored camera position.
+    fn subpixel_offset(&self) -> (f64, f64) { (0.0, 0.0) } // optional
+    /// Simulation speed multiplier read every frame (0.0 = hit-stop/pause,
+    /// 0.3 = bullet-time). Scaled time only affects how many fixed updates run.
+    fn time_scale(&self) -> f64 { 1.0 } // optional
+    /// Called once per frame with real (unscaled) elapsed time in `ctx.real_dt()`,
+    /// for UI/menus that must keep animating while gameplay is slowed or frozen
+    fn update_unscaled(&mut self, _ctx: &mut Context) {} // optional
+    /// Serialize whatever should survive a hot reload (see `hot_reload`)
+    fn save_state(&self) -> Vec<u8> { Vec::new() } // optional
+    /// Polled after every fixed update: hand over a pending Push/Pop/Replace
+    fn take_scene_change(&mut self) -> Option<SceneChange> { None } // optional
+    /// Polled after every fixed update: true has the engine call `on_load` again
+    /// (e.g. hot reload swapped in new code that needs its assets)
+    fn take_reload(&mut self) -> bool { false } // optional
+    /// Keep drawing the scene below this one (pause screens, dialogs)
+    fn is_overlay(&self) -> bool { false } // optional
+    /// The logical resolution changed; the framebuffer already has the new size
+    fn canvas_resized(&mut self, _w: u32, _h: u32) {} // optional
+    /// A hitch owed more fixed updates than `max_updates_per_frame`; this much
+    /// simulated time was skipped instead of caught up
+    fn time_dropped(&mut self, _seconds: f64) {} // optional
+    /// The window lost focus (held keys were already released)
+    fn on_focus_lost(&mut self) {} // optional
+    fn on_focus_gained(&mut self) {} // optional
+    /// The window was resized (screen pixels); the canvas keeps its resolution
+    fn on_resize(&mut self, _window_w: u32, _window_h: u32) {} // optional
+}
+
+/// Export a scene constructor from a hot-reloadable dylib crate. The closure
+/// receives the previous instance's `Scene::save_state` bytes (empty on first load).
+#[macro_export]
+macro_rules! export_scene {
+    ($ctor:expr) => {
+        #[no_mangle]
+        pub fn pix_create_scene(state: &[u8]) -> ::std::boxed::Box<dyn $crate::Scene> {
+            let ctor = $ctor;
+            ::std::boxed::Box::new(ctor(state))
+        }
+    };
+}
+
+/// How `Scene::update` is dispatched
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timestep {
+    /// Accumulator-driven updates of exactly this many seconds (default 1/60)
+    Fixed(f64),
+    /// One update per frame with the (scaled) real frame time, for pure visual effects
+    Variable,
+}
+impl Default for Timestep {
+    fn default() -> Self { Timestep::Fixed(FIXED_DT) }
+}
+
+/// Drawing policy when a frame's update+draw overruns its budget (one event-loop
+/// update, `1 / ups`). Fixed updates always run, so game time keeps pace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameSkip {
+    /// Draw every frame; game time slows once the catch-up limit is hit (default)
+    #[default]
+    Off,
+    /// After an over-budget frame, update without drawing for up to this many
+    /// frames in a row (the last drawn frame stays on screen)
+    Auto(u32),
+}
+
+/// Engine settings for `PixEngine::with_config`; `Default` matches `PixEngine::new`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineConfig {
+    pub title: String,
+    /// Initial window size in screen pixels
+    pub window_size: (u32, u32),
+    /// Logical canvas resolution (the PixelBuffer size), e.g. 256x240 or 480x270
+    pub canvas_size: (u32, u32),
+    pub fullscreen: bool,
+    pub fullscreen_mode: FullscreenMode,
+    pub scale_mode: ScaleMode,
+    /// Width/height of one canvas pixel on screen: 1.0 = square, 5.0 / 6.0 shows
+    /// 320x200 at 4:3 like DOS-era displays
+    pub pixel_aspect: f64,
+    /// Fill for the window area outside the canvas
+    pub letterbox_color: [u8; 4],
+    /// Bezel/border art drawn behind the canvas at canvas-pixel scale, centered
+    /// on it (leave the middle transparent or let the canvas cover it)
+    pub border_image: Option<RgbaImage>,
+    /// Window/taskbar icon (e.g. 32x32 or 64x64); `None` keeps the OS default
+    pub icon: Option<image::RgbaImage>,
+    /// Scanlines, vignette and color fringe run on every frame
+    pub post_fx: PostFx,
+    /// Display to open on and go fullscreen on (index from `PixEngine::monitors`);
+    /// `None` = wherever the OS puts the window
+    pub monitor: Option<usize>,
+    /// `None` leaves the platform default
+    pub vsync: Option<bool>,
+    /// Overrides `vsync`: 0 = off, 1 = every vblank, 2 = every other (30 FPS at 60 Hz)
+    pub swap_interval: Option<u32>,
+    /// Event-loop update events per second (the fixed step runs off these)
+    pub ups: u64,
+    /// Frame rate cap
+    pub max_fps: u64,
+    /// No window or GPU; drive the engine with `PixEngine::frame` (tests, CI)
+    pub headless: bool,
+    pub timestep: Timestep,
+    /// Catch-up limit for fixed updates in one frame (0 = unlimited); the rest
+    /// of the backlog is dropped so a hitch can't snowball
+    pub max_updates_per_frame: u32,
+    /// Skip drawing under load instead of falling behind
+    pub frame_skip: FrameSkip,
+    /// Stop fixed updates while the window is unfocused (`update_unscaled` and drawing continue)
+    pub pause_on_focus_loss: bool,
+    /// Alt+Enter / F11 toggle fullscreen (the keys aren't passed to the scene)
+    pub fullscreen_hotkeys: bool,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            title: "pix-engine".to_string(),
+            window_size: (LOW_W * 4, LOW_H * 4),
+            canvas_size: (LOW_W, LOW_H),
+            fullscreen: false,
+            fullscreen_mode: FullscreenMode::default(),
+            scale_mode: ScaleMode::default(),
+            pixel_aspect: 1.0,
+            letterbox_color: [18, 18, 20, 255],
+            border_image: None,
+            icon: None,
+            post_fx: PostFx::default(),
+            monitor: None,
+            vsync: None,
+            swap_interval: None,
+            ups: 120, // high logical UPS for smooth physics
+            max_fps: 60,
+            headless: false,
+            timestep: Timestep::default(),
+            max_updates_per_frame: 8,
+            frame_skip: FrameSkip::Off,
+            pause_on_focus_loss: false,
+            fullscreen_hotkeys: false,
+        }
+    }
+}
+
+/// Fluent construction: `PixEngine::builder().canvas(320, 180).window(1280, 720).title("..").build(scene)`
+#[derive(Clone, Debug, Default)]
+pub struct PixEngineBuilder {
+    config: EngineConfig,
+}
+
+impl PixEngineBuilder {
+    /// Logical canvas resolution
+    pub fn canvas(mut self, w: u32, h: u32) -> Self {
+        self.config.canvas_size = (w, h);
+        self
+    }
+    /// Initial window size
+    pub fn window(mut self, w: u32, h: u32) -> Self {
+        self.config.window_size = (w, h);
+        self
+    }
+    pub fn title(mut self, title: &str) -> Self {
+        self.config.title = title.to_string();
+        self
+    }
+    pub fn fullscreen(mut self, on: bool) -> Self {
+        self.config.fullscreen = on;
+        self
+    }
+    /// Borderless ("fullscreen desktop", default) or exclusive video mode
+    pub fn fullscreen_mode(mut self, mode: FullscreenMode) -> Self {
+        self.config.fullscreen_mode = mode;
+        self
+    }
+    /// Integer (default), fit, stretch or sharp-bilinear canvas scaling
+    pub fn scale_mode(mut self, mode: ScaleMode) -> Self {
+        self.config.scale_mode = mode;
+        self
+    }
+    /// Non-square pixels (width/height), e.g. `5.0 / 6.0` for 320x200 at 4:3
+    pub fn pixel_aspect(mut self, aspect: f64) -> Self {
+        self.config.pixel_aspect = aspect;
+        self
+    }
+    /// Color outside the scaled canvas
+    pub fn letterbox_color(mut self, c: [u8; 4]) -> Self {
+        self.config.letterbox_color = c;
+        self
+    }
+    /// Border art (CRT bezel etc.) around the canvas
+    pub fn border_image(mut self, img: RgbaImage) -> Self {
+        self.config.border_image = Some(img);
+        self
+    }
+    /// Window/taskbar icon
+    pub fn icon(mut self, icon: image::RgbaImage) -> Self {
+        self.config.icon = Some(icon);
+        self
+    }
+    /// Retro post effects (see `PostFx`)
+    pub fn post_fx(mut self, fx: PostFx) -> Self {
+        self.config.post_fx = fx;
+        self
+    }
+    /// Display index (see `PixEngine::monitors`)
+    pub fn monitor(mut self, index: usize) -> Self {
+        self.config.monitor = Some(index);
+        self
+    }
+    pub fn vsync(mut self, on: bool) -> Self {
+        self.config.vsync = Some(on);
+        self
+    }
+    /// Swap every `n`-th vblank (0 = no vsync); overrides `vsync`
+    pub fn swap_interval(mut self, n: u32) -> Self {
+        self.config.swap_interval = Some(n);
+        self
+    }
+    /// Run without a window (see `PixEngine::frame`)
+    pub fn headless(mut self) -> Self {
+        self.config.headless = true;
+        self
+    }
+    /// Fixed (default, 1/60 s) or variable update step
+    pub fn timestep(mut self, timestep: Timestep) -> Self {
+        self.config.timestep = timestep;
+        self
+    }
+    /// Fixed updates allowed per frame before time is dropped (default 8, 0 = unlimited)
+    pub fn max_updates_per_frame(mut self, n: u32) -> Self {
+        self.config.max_updates_per_frame = n;
+        self
+    }
+    /// Skip drawing frames when update+draw overruns the budget (default off)
+    pub fn frame_skip(mut self, policy: FrameSkip) -> Self {
+        self.config.frame_skip = policy;
+        self
+    }
+    /// Freeze the simulation while the window is unfocused
+    pub fn pause_on_focus_loss(mut self, on: bool) -> Self {
+        self.config.pause_on_focus_loss = on;
+        self
+    }
+    /// Let Alt+Enter / F11 toggle fullscreen
+    pub fn fullscreen_hotkeys(mut self, on: bool) -> Self {
+        self.config.fullscreen_hotkeys = on;
+        self
+    }
+    /// Event-loop update rate (default 120)
+    pub fn ups(mut self, ups: u64) -> Self {
+        self.config.ups = ups;
+        self
+    }
+    /// Frame rate cap (default 60)
+    pub fn max_fps(mut self, fps: u64) -> Self {
+        self.config.max_fps = fps;
+        self
+    }
+    /// Apply command-line overrides on top of everything set so far
+    pub fn args(mut self, args: &EngineArgs) -> Self {
+        self.config.apply_args(args);
+        self
+    }
+    /// Start from a complete config (e.g. loaded from a settings file)
+    pub fn config(mut self, config: EngineConfig) -> Self {
+        self.config = config;
+        self
+    }
+    pub fn build(self, scene: impl Scene + 'static) -> PixResult<PixEngine> {
+        PixEngine::with_config(self.config, scene)
+    }
+}
+
+impl EngineConfig {
+    /// Let command-line overrides win (`--scale` sizes the window from the canvas)
+    pub fn apply_args(&mut self, args: &EngineArgs) {
+        // saturating: `--scale` is user input and must not overflow the window size
+        if let Some(s) = args.scale { self.window_size = (self.canvas_size.0.saturating_mul(s), self.canvas_size.1.saturating_mul(s)); }
+        if let Some(fs) = args.fullscreen { self.fullscreen = fs; }
+        if args.vsync.is_some() { self.vsync = args.vsync; }
+    }
 }
 
+/// User effect run on each composed frame (`PixEngine::set_post_process`)
+type PostProcess = Box<dyn FnMut(&mut PixelBuffer)>;
+
 pub struct PixEngine {
-    window: PistonWindow,
-    scene: Box<dyn Scene>,
+    gpu: Option<Gpu>,
+    scenes: SceneManager,
     framebuffer: PixelBuffer,
+    /// Framebuffer after the post passes; the scene's canvas is left untouched
+    /// so effects don't stack on scenes that don't clear every frame
+    output: PixelBuffer,
+    /// `output` holds the presented frame (false: no pass ran, `framebuffer` does)
+    post_active: bool,
+    ctx: Context,
+    viewports: Vec<Viewport>,
+    next_viewport: u32,
+    layers: Compositor,
+    smooth_scroll: bool,
+    /// Draw with the camera snapped and present its remainder (see `set_smooth_camera`)
+    smooth_camera: bool,
+    /// `smooth_scroll` as it was before `set_smooth_camera(true)`
+    scroll_before_camera: bool,
+    camera_frac: (f64, f64),
+    debug: DebugTime,
+    color_filter: ColorFilter,
+    color_lut: Option<(lut::ColorLut, u8)>,
+    post_fx: PostFx,
+    post_process: Option<PostProcess>,
+    canvas: (u32, u32),
+    timestep: Timestep,
+    max_updates: u32,
+    frame_skip: FrameSkip,
+    /// Seconds the last drawn frame's update+draw took, and skips since
+    frame_cost: f64,
+    skipped_in_row: u32,
+    pause_unfocused: bool,
+    focused: bool,
+    window_size: (u32, u32),
+    ups: u64,
+    max_fps: u64,
+    fullscreen: bool,
+    fullscreen_mode: FullscreenMode,
+    monitor: Option<usize>,
+    swap_interval: Option<u32>,
+    scale_mode: ScaleMode,
+    pixel_aspect: f64,
+    letterbox: [u8; 4],
+    fullscreen_hotkeys: bool,
+    acc: f64, // fixed-step accumulator
+    quit: bool,
+}
+
+/// Window and GPU side of the engine; absent in headless mode
+struct Gpu {
+    window: PistonWindow,
     tex_ctx: G2dTextureContext,
     tex: G2dTexture,
-    pressed: HashSet<Key>,
+    /// `ScaleMode::SharpBilinear`: framebuffer nearest-upscaled by an integer
+    /// factor on the CPU, drawn with a bilinear-filtered texture
+    prescaled: Option<(u32, (u32, u32), G2dTexture)>,
+    /// Border art and its size in canvas pixels
+    border: Option<(G2dTexture, (u32, u32))>,
+    /// What `tex` holds, to upload only the changed region
+    shown: UploadState,
+    /// Camera-visible part of `Context::world`
+    world: Option<WorldTex>,
 }
 
-impl PixEngine {
-    pub fn new(window_width: u32, window_height: u32, window_title: &str, mut scene: impl Scene + 'static ) -> Self {
-        let mut window: PistonWindow = WindowSettings::new(window_title, [window_width, window_height])
-            .exit_on_esc(true)
+/// Texture sized to the camera view that the visible world region streams into
+struct WorldTex {
+    tex: G2dTexture,
+    size: (u32, u32),
+    /// World rect currently in the texture's top-left corner
+    shown: Option<Rect>,
+}
+
+impl Gpu {
+    fn new(config: &EngineConfig, fb: &PixelBuffer) -> PixResult<Self> {
+        let mut settings = WindowSettings::new(config.title.as_str(), [config.window_size.0, config.window_size.1])
+            .exit_on_esc(true);
+        if let Some(vs) = config.vsync { settings = settings.vsync(vs); }
+        let mut window: PistonWindow = settings
             .build()
-            .unwrap();
-        window.set_ups(120);   // high logical UPS for smooth physics
-        window.set_max_fps(60);
-        let fb = PixelBuffer::new(LOW_W, LOW_H);
+            .map_err(|e| PixError::Window(e.to_string()))?;
+        window.set_ups(config.ups);
+        window.set_max_fps(config.max_fps);
+        if let Some(n) = config.swap_interval {
+            display::set_swap_interval(&window.window.surface, &window.window.ctx, n)?;
+        }
+        if let Some(icon) = &config.icon { display::set_icon(&window.window.window, icon)?; }
+        if let Some(m) = config.monitor { display::move_to_monitor(&window.window.window, m); }
+        if config.fullscreen {
+            display::set_fullscreen(&window.window.window, true, config.fullscreen_mode, config.monitor);
+        }
         let mut tex_ctx = window.create_texture_context();
-        let tex = make_nearest_texture( & mut tex_ctx, & fb.buf);
-        let pressed = HashSet::new();
+        let tex = buffer_texture( & mut tex_ctx, fb)?;
+        let mut gpu = Self { window, tex_ctx, tex, prescaled: None, border: None, shown: UploadState::new(), world: None };
+        if let Some(img) = &config.border_image { gpu.set_border(Some(img))?; }
+        Ok(gpu)
+    }
+    fn set_border(&mut self, img: Option<&RgbaImage>) -> PixResult<()> {
+        self.border = match img {
+            Some(img) => Some((make_nearest_texture(&mut self.tex_ctx, img)?, img.dimensions())),
+            None => None,
+        };
+        Ok(())
+    }
+    /// Upload only the part of `fb` that changed since the last upload (nothing
+    /// on an unchanged frame, everything after a resize)
+    fn upload(&mut self, fb: &PixelBuffer, scratch: &Scratch) -> PixResult<()> {
+        let Some((r, data)) = self.shown.stage(fb, scratch) else { return Ok(()) };
+        let res = UpdateTexture::update(&mut self.tex, &mut self.tex_ctx, Format::Rgba8, data, [r.x as u32, r.y as u32], [r.w, r.h]);
+        if let Err(e) = res {
+            self.shown.invalidate();
+            return Err(PixError::Texture(format!("upload: {e:?}")));
+        }
+        Ok(())
+    }
+}
+
+/// CPU half of the per-frame texture upload: remembers the pixels the texture
+/// holds and stages only the region that changed. Public for the benchmarks.
+#[derive(Clone, Debug, Default)]
+pub struct UploadState {
+    /// Pixels the texture holds (empty = unknown)
+    shown: Vec<u32>,
+}
+
+impl UploadState {
+    pub fn new() -> Self { Self::default() }
+    /// Forget what the texture holds, so the next `stage` sends everything
+    pub fn invalidate(&mut self) { self.shown.clear(); }
+    /// Changed region of `fb` and its RGBA bytes (a whole frame is borrowed, a
+    /// partial one copied into `scratch`), now recorded as shown; `None` if unchanged
+    pub fn stage<'a>(&mut self, fb: &'a PixelBuffer, scratch: &'a Scratch) -> Option<(Rect, &'a [u8])> {
+        let r = fb.diff_pixels(&self.shown)?;
+        if r == Rect::new(0, 0, fb.w, fb.h) {
+            self.shown.clear();
+            self.shown.extend_from_slice(&fb.buf);
+            return Some((r, fb.as_bytes()));
+        }
+        let (stride, n) = (fb.w as usize * 4, r.w as usize * 4);
+        let data = scratch.alloc::<u8>(n * r.h as usize);
+        for (y, out) in (r.y as usize..).zip(data.chunks_exact_mut(n)) {
+            out.copy_from_slice(&fb.as_bytes()[y * stride + r.x as usize * 4..][..n]);
+        }
+        let (w, x0, n) = (fb.w as usize, r.x as usize, r.w as usize);
+        for y in r.y as usize..r.bottom() as usize {
+            self.shown[y * w + x0..][..n].copy_from_slice(&fb.buf[y * w + x0..][..n]);
+        }
+        Some((r, data))
+    }
+}
+
+/// Debug clock controls layered on the fixed-step accumulator
+#[derive(Clone, Copy, Debug)]
+struct DebugTime {
+    hotkeys: bool,
+    paused: bool,
+    steps: u32, // fixed steps still to run while paused
+    speed: f64,
+}
+
+impl PixEngine {
+    pub fn builder() -> PixEngineBuilder { PixEngineBuilder::default() }
+
+    pub fn new(window_width: u32, window_height: u32, window_title: &str, scene: impl Scene + 'static ) -> PixResult<Self> {
+        let config = EngineConfig {
+            title: window_title.to_string(),
+            window_size: (window_width, window_height),
+            ..EngineConfig::default()
+        };
+        Self::with_config(config, scene)
+    }
+
+    /// Like `new`, but command-line overrides (see `cli`) win over the given settings
+    pub fn with_args(window_width: u32, window_height: u32, window_title: &str, scene: impl Scene + 'static, args: &EngineArgs) -> PixResult<Self> {
+        let mut config = EngineConfig {
+            title: window_title.to_string(),
+            window_size: (window_width, window_height),
+            ..EngineConfig::default()
+        };
+        config.apply_args(args);
+        Self::with_config(config, scene)
+    }
+
+    pub fn with_config(config: EngineConfig, mut scene: impl Scene + 'static) -> PixResult<Self> {
+        let (canvas_w, canvas_h) = (config.canvas_size.0.max(1), config.canvas_size.1.max(1));
+        let fb = PixelBuffer::new(canvas_w, canvas_h);
+        let mut gpu = if config.headless { None } else { Some(Gpu::new(&config, &fb)?) };
+        let window_size = gpu.as_ref().map_or(config.window_size, |g| g.window.size().into());
         // Give the game a chance to load assets safely (no double &mut)
         {
-            let mut assets = Assets { tex_ctx: &mut tex_ctx };
+            let mut assets = Assets { tex_ctx: gpu.as_mut().map(|g| &mut g.tex_ctx) };
             scene.on_load(&mut assets);
         }
-        Self { window, scene: Box::new(scene), framebuffer: fb, tex_ctx, tex, pressed }
-       
+        Ok(Self {
+            gpu,
+            scenes: SceneManager::new(Box::new(scene)),
+            framebuffer: fb,
+            output: PixelBuffer::new(0, 0),
+            post_active: false,
+            ctx: Context::new(canvas_w, canvas_h),
+            viewports: Vec::new(),
+            next_viewport: 0,
+            layers: Compositor::new(),
+            smooth_scroll: false,
+            smooth_camera: false,
+            scroll_before_camera: false,
+            camera_frac: (0.0, 0.0),
+            debug: DebugTime { hotkeys: false, paused: false, steps: 0, speed: 1.0 },
+            color_filter: ColorFilter::None,
+            color_lut: None,
+            post_fx: config.post_fx,
+            post_process: None,
+            canvas: (canvas_w, canvas_h),
+            timestep: config.timestep,
+            max_updates: config.max_updates_per_frame,
+            frame_skip: config.frame_skip,
+            frame_cost: 0.0,
+            skipped_in_row: 0,
+            pause_unfocused: config.pause_on_focus_loss,
+            focused: true,
+            window_size,
+            ups: config.ups,
+            max_fps: config.max_fps,
+            fullscreen: config.fullscreen && !config.headless,
+            fullscreen_mode: config.fullscreen_mode,
+            monitor: config.monitor,
+            swap_interval: config.swap_interval.or(config.vsync.map(u32::from)),
+            scale_mode: config.scale_mode,
+            pixel_aspect: config.pixel_aspect,
+            letterbox: config.letterbox_color,
+            fullscreen_hotkeys: config.fullscreen_hotkeys,
+            acc: 0.0,
+            quit: false,
+        })
     }
     
-    pub fn load_sprite_atlas(&mut self, path: &str) -> G2dTexture {
-        Texture::from_path(
-            &mut self.tex_ctx,
-            path,
-            Flip::None,
-            &TextureSettings::new().filter(Filter::Nearest),
-       ).expect("Sprite atlas loading failed!")
+    pub fn load_sprite_atlas(&mut self, path: &str) -> PixResult<G2dTexture> {
+        self.assets().load_texture(path)
+    }
+
+    fn assets(&mut self) -> Assets<'_> {
+        Assets { tex_ctx: self.gpu.as_mut().map(|g| &mut g.tex_ctx) }
     }
+    /// True when built without a window (`EngineConfig::headless`)
+    #[inline] pub fn is_headless(&self) -> bool { self.gpu.is_none() }
+    /// The last composed frame, post passes included
+    #[inline] pub fn framebuffer(&self) -> &PixelBuffer {
+        if self.post_active { &self.output } else { &self.framebuffer }
+    }
+    /// True once a scene sent `EngineCommand::Quit` or the root scene popped itself
+    #[inline] pub fn should_quit(&self) -> bool { self.quit || self.scenes.is_finished() }
 
-    pub fn run(&mut self) {
-        let mut acc = 0.0;
+    /// Render `scene` live into a (x, y, w, h) sub-rectangle of the framebuffer.
+    /// Viewports are composited in insertion order, on top of the main scene.
+    pub fn add_viewport(&mut self, x: i32, y: i32, w: u32, h: u32, mut scene: impl Scene + 'static) -> ViewportId {
+        let id = ViewportId(self.next_viewport);
+        self.next_viewport += 1;
+        scene.on_load(&mut self.assets());
+        self.viewports.push(Viewport::new(id, x, y, w, h, Box::new(scene)));
+        id
+    }
+    pub fn remove_viewport(&mut self, id: ViewportId) -> bool {
+        let before = self.viewports.len();
+        self.viewports.retain(|v| v.id != id);
+        self.viewports.len() != before
+    }
+    pub fn viewport_mut(&mut self, id: ViewportId) -> Option<&mut Viewport> {
+        self.viewports.iter_mut().find(|v| v.id == id)
+    }
 
-        let [win_w, win_h]: [u32; 2] = self.window.size().into();
+    /// Add a canvas-sized layer on top of the stack. Once any layer exists the
+    /// engine clears the layers each frame, calls `Scene::draw_layers` after
+    /// `Scene::draw`, and composites them over the framebuffer.
+    pub fn add_layer(&mut self, name: &str) -> &mut layers::Layer {
+        let idx = self.layers.add_layer(name, self.framebuffer.w, self.framebuffer.h);
+        self.layers.layer_mut_at(idx)
+    }
+    /// Add a layer drawn as batched GPU quads from `texture` (usually an
+    /// uploaded `Atlas`); queue sprites in `Scene::draw_layers` via
+    /// `Compositor::batch_mut`. Not drawn by headless `frame()`.
+    pub fn add_gpu_layer(&mut self, name: &str, texture: G2dTexture) -> &mut layers::Layer {
+        let layer = self.add_layer(name);
+        layer.use_gpu(texture);
+        layer
+    }
+    pub fn layers_mut(&mut self) -> &mut Compositor { &mut self.layers }
 
+    /// Smooth sub-pixel scrolling: the framebuffer grows by one pixel in each
+    /// direction and the scene's `subpixel_offset` shifts it during upscale,
+    /// cropped to the canvas, so slow scrolls glide instead of stepping.
+    pub fn set_smooth_scroll(&mut self, on: bool) -> PixResult<()> {
+        if on == self.smooth_scroll { return Ok(()); }
+        let prev = self.buffer_size();
+        self.smooth_scroll = on;
+        if let Err(e) = self.resize_buffers(prev) {
+            // back to the old size so the engine isn't left half-switched
+            let attempted = self.buffer_size();
+            self.smooth_scroll = !on;
+            let _ = self.resize_buffers(attempted);
+            return Err(e);
+        }
+        Ok(())
+    }
+    /// Switch the logical resolution at runtime (e.g. 320x180 <-> 640x360 from
+    /// an options menu). Framebuffer, texture and layers are reallocated and the
+    /// scenes get `Scene::canvas_resized`; the window keeps its size. The only
+    /// resize path: `EngineCommand::SetCanvasSize` and a scene resizing the
+    /// framebuffer itself both end up here.
+    pub fn set_canvas_size(&mut self, w: u32, h: u32) -> PixResult<()> {
+        let (w, h) = (w.max(1), h.max(1));
+        let prev = self.buffer_size();
+        let changed = (w, h) != self.canvas;
+        self.canvas = (w, h);
+        self.resize_buffers(prev)?;
+        if changed {
+            self.ctx.sync_canvas_size(w, h);
+            self.scenes.canvas_resized(w, h);
+        }
+        Ok(())
+    }
+    /// Current logical canvas resolution
+    #[inline] pub fn canvas_size(&self) -> (u32, u32) { self.canvas }
+    /// Timing, input, camera and sprites shared with the scenes
+    #[inline] pub fn context(&self) -> &Context { &self.ctx }
+    #[inline] pub fn context_mut(&mut self) -> &mut Context { &mut self.ctx }
+    /// Achieved FPS/UPS and frame times (also `Context::stats`)
+    #[inline] pub fn stats(&self) -> &stats::Stats { self.ctx.stats() }
 
-        while let Some(e) = self.window.next() {
-            if let Some(btn) = e.press_args() {
-                if let Button::Keyboard(k) = btn {
-                    // Ignore key-repeat: insert returns false if it was already down
-                    if self.pressed.insert(k) {
-                        // scene key-down callback (optional)
-                        self.scene.key_event(k, true);
-                    }
-                }
+    /// Framebuffer size for the canvas: the one place that adds the smooth-scroll
+    /// row and column
+    #[inline]
+    fn buffer_size(&self) -> (u32, u32) {
+        let extra = self.smooth_scroll as u32;
+        (self.canvas.0 + extra, self.canvas.1 + extra)
+    }
+    /// Bring framebuffer, canvas-sized layers (`prev` in size) and texture to `buffer_size`
+    fn resize_buffers(&mut self, prev: (u32, u32)) -> PixResult<()> {
+        let size = self.buffer_size();
+        if (self.framebuffer.w, self.framebuffer.h) == size && prev == size { return Ok(()); }
+        self.framebuffer.resize(size.0, size.1);
+        self.layers.resize_matching(prev, size);
+        self.recreate_texture()
+    }
+    /// Pick up a framebuffer resized by the scene (`PixelBuffer::resize`): its
+    /// new size is the requested canvas size
+    fn sync_canvas_size(&mut self) -> PixResult<()> {
+        let (fw, fh) = (self.framebuffer.w, self.framebuffer.h);
+        if (fw, fh) == self.buffer_size() { return Ok(()); }
+        self.set_canvas_size(fw, fh)
+    }
+    /// New GPU texture matching the framebuffer size (no-op when headless)
+    fn recreate_texture(&mut self) -> PixResult<()> {
+        if let Some(gpu) = self.gpu.as_mut() {
+            gpu.tex = buffer_texture(&mut gpu.tex_ctx, &self.framebuffer)?;
+            gpu.shown.invalidate();
+        }
+        Ok(())
+    }
+    #[inline] pub fn smooth_scroll(&self) -> bool { self.smooth_scroll }
+    /// Sub-pixel camera smoothing: turns on `smooth_scroll`, has scenes draw
+    /// with `ctx.camera` snapped to whole canvas pixels and shifts the upscaled
+    /// canvas by the dropped fraction, so the camera glides at low resolutions.
+    /// Replaces `Scene::subpixel_offset`; a rotated camera isn't shifted.
+    /// Turning it off restores the `smooth_scroll` setting from before.
+    pub fn set_smooth_camera(&mut self, on: bool) -> PixResult<()> {
+        if on == self.smooth_camera { return Ok(()); }
+        if on {
+            let before = self.smooth_scroll;
+            self.set_smooth_scroll(true)?;
+            self.scroll_before_camera = before;
+        } else {
+            self.set_smooth_scroll(self.scroll_before_camera)?;
+        }
+        self.smooth_camera = on;
+        self.camera_frac = (0.0, 0.0);
+        Ok(())
+    }
+    #[inline] pub fn smooth_camera(&self) -> bool { self.smooth_camera }
+
+    /// Enable debug hotkeys: F6 pause/resume, F7 advance one fixed step while
+    /// paused, F8 cycle speed 1x/0.5x/0.25x. Handled keys aren't passed to the scene.
+    pub fn set_debug_hotkeys(&mut self, on: bool) { self.debug.hotkeys = on; }
+    /// Freeze the simulation (drawing continues)
+    pub fn set_debug_paused(&mut self, paused: bool) {
+        self.debug.paused = paused;
+        self.debug.steps = 0;
+    }
+    #[inline] pub fn debug_paused(&self) -> bool { self.debug.paused }
+    /// Run exactly one fixed update on the next frame (pauses if running)
+    pub fn debug_step(&mut self) {
+        self.debug.paused = true;
+        self.debug.steps += 1;
+    }
+    /// Slow-motion multiplier for the accumulator (e.g. 0.25, 0.5, 1.0)
+    pub fn set_debug_speed(&mut self, speed: f64) { self.debug.speed = speed.max(0.0); }
+    #[inline] pub fn debug_speed(&self) -> f64 { self.debug.speed }
+
+    fn debug_hotkey(&mut self, k: Key) -> bool {
+        if !self.debug.hotkeys { return false; }
+        match k {
+            Key::F6 => { let p = !self.debug.paused; self.set_debug_paused(p); }
+            Key::F7 => self.debug_step(),
+            Key::F8 => {
+                let next = if self.debug.speed > 0.5 { 0.5 } else if self.debug.speed > 0.25 { 0.25 } else { 1.0 };
+                self.set_debug_speed(next);
             }
-            if let Some(btn) = e.release_args() {
-                if let Button::Keyboard(k) = btn {
-                    if self.pressed.remove(&k) {
-                        // scene key-up callback (optional)
-                        self.scene.key_event(k, false);
-                    }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Freeze the simulation while the window is unfocused
+    pub fn set_pause_on_focus_loss(&mut self, on: bool) { self.pause_unfocused = on; }
+    #[inline] pub fn pause_on_focus_loss(&self) -> bool { self.pause_unfocused }
+    #[inline] pub fn is_focused(&self) -> bool { self.focused }
+    /// Event-loop update rate; takes effect immediately
+    pub fn set_ups(&mut self, ups: u64) {
+        self.ups = ups;
+        if let Some(gpu) = self.gpu.as_mut() { gpu.window.set_ups(ups); }
+    }
+    #[inline] pub fn ups(&self) -> u64 { self.ups }
+    /// Frame rate cap (e.g. from a settings menu); takes effect immediately
+    pub fn set_max_fps(&mut self, fps: u64) {
+        self.max_fps = fps;
+        if let Some(gpu) = self.gpu.as_mut() { gpu.window.set_max_fps(fps); }
+    }
+    #[inline] pub fn max_fps(&self) -> u64 { self.max_fps }
+    /// Switch between windowed and fullscreen without restarting; the upscale
+    /// follows on the resulting resize
+    pub fn set_fullscreen(&mut self, on: bool) {
+        let Some(gpu) = self.gpu.as_mut() else { return };
+        self.fullscreen = on;
+        display::set_fullscreen(&gpu.window.window.window, on, self.fullscreen_mode, self.monitor);
+        let (w, h) = gpu.window.size().into();
+        self.set_window_size(w, h);
+    }
+    pub fn toggle_fullscreen(&mut self) { self.set_fullscreen(!self.fullscreen); }
+    #[inline] pub fn is_fullscreen(&self) -> bool { self.fullscreen }
+    /// Mode used by the next switch into fullscreen (re-enters if already fullscreen)
+    pub fn set_fullscreen_mode(&mut self, mode: FullscreenMode) {
+        self.fullscreen_mode = mode;
+        if self.fullscreen { self.set_fullscreen(true); }
+    }
+    #[inline] pub fn fullscreen_mode(&self) -> FullscreenMode { self.fullscreen_mode }
+    /// Connected displays (empty when headless)
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.gpu.as_ref().map_or_else(Vec::new, |g| display::monitors(&g.window.window.window))
+    }
+    /// Move the window (or the fullscreen target) to display `index`; `None`
+    /// goes back to following the window
+    pub fn set_monitor(&mut self, index: Option<usize>) {
+        self.monitor = index;
+        if self.fullscreen {
+            self.set_fullscreen(true);
+        } else if let (Some(i), Some(gpu)) = (index, self.gpu.as_ref()) {
+            display::move_to_monitor(&gpu.window.window.window, i);
+        }
+    }
+    #[inline] pub fn monitor(&self) -> Option<usize> { self.monitor }
+    /// Replace the window/taskbar icon (no-op when headless)
+    pub fn set_window_icon(&mut self, icon: &image::RgbaImage) -> PixResult<()> {
+        match self.gpu.as_ref() {
+            Some(gpu) => display::set_icon(&gpu.window.window.window, icon),
+            None => Ok(()),
+        }
+    }
+    /// Turn vsync on/off at runtime (tearing vs latency)
+    pub fn set_vsync(&mut self, on: bool) -> PixResult<()> { self.set_swap_interval(on as u32) }
+    /// Swap every `n`-th vblank, 0 = don't wait. Adaptive (late-swap tearing)
+    /// sync isn't offered by the GL backend.
+    pub fn set_swap_interval(&mut self, n: u32) -> PixResult<()> {
+        if let Some(gpu) = self.gpu.as_ref() {
+            display::set_swap_interval(&gpu.window.window.surface, &gpu.window.window.ctx, n)?;
+        }
+        self.swap_interval = Some(n);
+        Ok(())
+    }
+    /// `None` = platform default (never set)
+    #[inline] pub fn swap_interval(&self) -> Option<u32> { self.swap_interval }
+    /// Alt+Enter / F11 toggle fullscreen
+    pub fn set_fullscreen_hotkeys(&mut self, on: bool) { self.fullscreen_hotkeys = on; }
+
+    fn fullscreen_hotkey(&mut self, k: Key) -> bool {
+        if !self.fullscreen_hotkeys { return false; }
+        let alt = self.ctx.input.is_down(Key::LAlt) || self.ctx.input.is_down(Key::RAlt);
+        if k == Key::F11 || (k == Key::Return && alt) {
+            self.toggle_fullscreen();
+            return true;
+        }
+        false
+    }
+
+    /// Current window size in screen pixels
+    #[inline] pub fn window_size(&self) -> (u32, u32) { self.window_size }
+    /// Window resized (also callable headless to simulate it)
+    pub fn set_window_size(&mut self, w: u32, h: u32) {
+        if (w, h) == self.window_size { return; }
+        self.window_size = (w, h);
+        self.scenes.window_resized(w, h);
+    }
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) { self.scale_mode = mode; }
+    #[inline] pub fn scale_mode(&self) -> ScaleMode { self.scale_mode }
+    /// Width/height of a canvas pixel on screen (1.0 = square)
+    pub fn set_pixel_aspect(&mut self, aspect: f64) { self.pixel_aspect = aspect; }
+    #[inline] pub fn pixel_aspect(&self) -> f64 { self.pixel_aspect }
+    /// Color outside the scaled canvas
+    pub fn set_letterbox_color(&mut self, c: [u8; 4]) { self.letterbox = c; }
+    #[inline] pub fn letterbox_color(&self) -> [u8; 4] { self.letterbox }
+    /// Border art drawn behind the canvas at canvas-pixel scale, centered on
+    /// it; `None` removes it (no-op when headless)
+    pub fn set_border_image(&mut self, img: Option<&RgbaImage>) -> PixResult<()> {
+        match self.gpu.as_mut() {
+            Some(gpu) => gpu.set_border(img),
+            None => Ok(()),
+        }
+    }
+    /// The (x, y, w, h) screen rectangle the canvas is presented in, centered
+    /// in the window according to the scale mode
+    pub fn present_rect(&self) -> [f64; 4] {
+        let (win_w, win_h) = self.window_size;
+        let (canvas_w, canvas_h) = self.canvas;
+        let (sx, sy) = self.scale_mode.scale(self.window_size, self.canvas, self.pixel_aspect);
+        let draw_w = canvas_w as f64 * sx;
+        let draw_h = canvas_h as f64 * sy;
+        let off_x = ((win_w as f64 - draw_w) * 0.5).floor();
+        let off_y = ((win_h as f64 - draw_h) * 0.5).floor();
+        [off_x, off_y, draw_w, draw_h]
+    }
+    /// Window focus changed (also callable headless to simulate it)
+    pub fn set_focused(&mut self, focused: bool) {
+        if focused == self.focused { return; }
+        self.focused = focused;
+        if focused {
+            self.scenes.top_mut().on_focus_gained();
+        } else {
+            // clear keys to avoid “stuck key” bugs
+            self.ctx.input.clear();
+            if let Some(p) = &mut self.ctx.players { p.release_all(); }
+            self.scenes.top_mut().on_focus_lost();
+        }
+    }
+
+    /// Accessibility filter applied to every frame before upload
+    pub fn set_color_filter(&mut self, filter: ColorFilter) { self.color_filter = filter; }
+    #[inline] pub fn color_filter(&self) -> ColorFilter { self.color_filter }
+    /// Color grade applied to every frame before the accessibility filter
+    pub fn set_color_lut(&mut self, lut: Option<lut::ColorLut>) { self.color_lut = lut.map(|l| (l, 255)); }
+    /// Custom CPU effect run on every composed frame: after drawing, transitions
+    /// and palette cycling, before the color grade, `PostFx` and upload. It gets
+    /// a copy of the canvas, so changes don't carry over into the next frame.
+    pub fn set_post_process(&mut self, f: impl FnMut(&mut PixelBuffer) + 'static) {
+        self.post_process = Some(Box::new(f));
+    }
+    pub fn clear_post_process(&mut self) { self.post_process = None; }
+    /// Scanlines, vignette and color fringe, run after the color grade
+    pub fn set_post_fx(&mut self, fx: PostFx) { self.post_fx = fx; }
+    #[inline] pub fn post_fx(&self) -> PostFx { self.post_fx }
+    /// Strength of the color grade (0 = off, 255 = full), for fading moods in and out
+    pub fn set_color_lut_amount(&mut self, amount: u8) {
+        if let Some((_, a)) = &mut self.color_lut { *a = amount; }
+    }
+
+    /// Write a crash dump (last frame, log, recent input) below `dir` on panic
+    pub fn enable_crash_dumps(&mut self, dir: impl AsRef<std::path::Path>) {
+        crash::install(dir);
+        crash::log("crash dumps enabled");
+    }
+
+    /// Switch between fixed and variable updates at runtime
+    pub fn set_timestep(&mut self, timestep: Timestep) {
+        self.timestep = timestep;
+        self.acc = 0.0;
+    }
+    #[inline] pub fn timestep(&self) -> Timestep { self.timestep }
+    /// Catch-up limit for fixed updates per frame (0 = unlimited)
+    pub fn set_max_updates_per_frame(&mut self, n: u32) { self.max_updates = n; }
+    #[inline] pub fn max_updates_per_frame(&self) -> u32 { self.max_updates }
+    /// Skip drawing under load (see `FrameSkip`); headless `frame` always draws
+    pub fn set_frame_skip(&mut self, policy: FrameSkip) {
+        self.frame_skip = policy;
+        self.skipped_in_row = 0;
+    }
+    #[inline] pub fn frame_skip(&self) -> FrameSkip { self.frame_skip }
+    /// Whether the current frame goes undrawn under the frame-skip policy
+    fn skip_frame(&mut self) -> bool {
+        let FrameSkip::Auto(max) = self.frame_skip else { return false };
+        let budget = 1.0 / self.ups.max(1) as f64;
+        if self.frame_cost > budget && self.skipped_in_row < max {
+            self.skipped_in_row += 1;
+            true
+        } else {
+            self.skipped_in_row = 0;
+            false
+        }
+    }
+    /// Length of one fixed update (also used for debug steps in variable mode)
+    fn fixed_dt(&self) -> f64 {
+        match self.timestep {
+            Timestep::Fixed(dt) if dt > 0.0 => dt,
+            _ => FIXED_DT,
+        }
+    }
+
+    /// Global simulation speed, multiplied with `Scene::time_scale` and the debug
+    /// speed; scenes set the same value through `Context::set_time_scale`
+    pub fn set_time_scale(&mut self, scale: f64) { self.ctx.set_time_scale(scale); }
+    #[inline] pub fn time_scale(&self) -> f64 { self.ctx.time_scale() }
+
+    /// One fixed simulation step for the scene and its viewports
+    fn tick(&mut self, dt: f64) -> PixResult<()> {
+        // scenes are frozen while a transition fades out
+        self.ctx.begin_tick(dt);
+        if !self.scenes.is_holding() {
+            self.scenes.top_mut().update(&mut self.ctx, &mut self.framebuffer);
+            if self.scenes.top_mut().take_reload() {
+                let mut assets = Assets { tex_ctx: self.gpu.as_mut().map(|g| &mut g.tex_ctx) };
+                self.scenes.top_mut().on_load(&mut assets);
+            }
+            if let Some(change) = self.scenes.top_mut().take_scene_change() {
+                let mut assets = Assets { tex_ctx: self.gpu.as_mut().map(|g| &mut g.tex_ctx) };
+                self.scenes.request(change, &mut assets, &self.framebuffer);
+            }
+        }
+        self.ctx.end_tick();
+        for vp in &mut self.viewports { vp.update(dt); }
+        self.apply_commands()
+    }
+
+    /// Carry out `EngineCommand`s queued by the scene (and viewport scenes)
+    fn apply_commands(&mut self) -> PixResult<()> {
+        let mut cmds = self.ctx.take_commands();
+        for vp in &mut self.viewports { cmds.extend(vp.ctx.take_commands()); }
+        for cmd in cmds {
+            match cmd {
+                EngineCommand::Quit => self.quit = true,
+                EngineCommand::SetCanvasSize(w, h) => self.set_canvas_size(w, h)?,
+                EngineCommand::SetMaxFps(fps) => self.set_max_fps(fps),
+                // window-only commands are ignored when headless
+                _ if self.gpu.is_none() => {}
+                EngineCommand::SetTitle(t) => self.gpu().window.set_title(t),
+                EngineCommand::SetFullscreen(on) => self.set_fullscreen(on),
+                EngineCommand::ShowCursor(on) => self.gpu().window.window.window.set_cursor_visible(on),
+            }
+        }
+        Ok(())
+    }
+
+    fn gpu(&mut self) -> &mut Gpu { self.gpu.as_mut().expect("windowed engine") }
+
+    /// Feed a key press/release as if it came from the window (headless tests).
+    /// Key-repeat and releases of keys that weren't down are dropped.
+    pub fn send_key(&mut self, k: Key, down: bool) {
+        let changed = if down { self.ctx.input.press(k) } else { self.ctx.input.release(k) };
+        if changed {
+            crash::record_input(k, down);
+            if let Some(p) = &mut self.ctx.players { p.handle_key(k, down); }
+            self.scenes.top_mut().key_event(k, down);
+        }
+    }
+
+    /// Feed a gamepad button as if it came from the window (headless tests)
+    pub fn send_gamepad_button(&mut self, id: u32, button: u8, down: bool) {
+        if let Some(p) = &mut self.ctx.players { p.handle_button(id, button, down); }
+        self.scenes.top_mut().gamepad_button(id, button, down);
+    }
+    /// Feed a gamepad axis as if it came from the window (headless tests)
+    pub fn send_gamepad_axis(&mut self, id: u32, axis: u8, position: f64) {
+        self.ctx.gamepads.handle_axis(id, axis, position);
+        if let Some(p) = &mut self.ctx.players { p.handle_axis(id, axis, position); }
+        self.scenes.top_mut().gamepad_axis(id, axis, position);
+    }
+
+    /// Advance `dt` real seconds and draw one frame into the framebuffer without
+    /// presenting it. This is the whole loop in headless mode.
+    pub fn frame(&mut self, dt: f64) -> PixResult<&PixelBuffer> {
+        self.update_frame(dt)?;
+        self.compose_frame()?;
+        Ok(self.framebuffer())
+    }
+
+    /// Run exactly one update of `dt` seconds (usually `1.0 / 60.0`), ignoring
+    /// the accumulator, time scale and debug pause. For tooling and tests that
+    /// advance the engine deterministically; follow with `render` to draw.
+    pub fn step(&mut self, dt: f64) -> PixResult<()> {
+        self.ctx.begin_frame(dt);
+        self.ctx.set_alpha(0.0);
+        self.tick(dt)?;
+        self.scenes.top_mut().update_unscaled(&mut self.ctx);
+        self.apply_commands()?;
+        self.sync_canvas_size()
+    }
+
+    /// Draw the current state into the framebuffer (and upload it to the GPU
+    /// when there is a window) without advancing time
+    pub fn render(&mut self) -> PixResult<&PixelBuffer> {
+        self.compose_frame()?;
+        if self.gpu.is_some() && self.scale_mode == ScaleMode::SharpBilinear {
+            self.upload_prescaled()?;
+        } else if let Some(gpu) = self.gpu.as_mut() {
+            let _p = profile::scope("texture_upload");
+            gpu.prescaled = None;
+            let fb = if self.post_active { &self.output } else { &self.framebuffer };
+            gpu.upload(fb, self.ctx.scratch())?;
+        }
+        if self.gpu.is_some() { self.upload_world()?; }
+        Ok(self.framebuffer())
+    }
+
+    /// Stream the camera-visible part of `Context::world` into a view-sized
+    /// texture; skipped when neither the world nor the visible rect changed
+    fn upload_world(&mut self) -> PixResult<()> {
+        let dirty = self.ctx.take_world_dirty();
+        let gpu = self.gpu.as_mut().expect("windowed engine");
+        let Some(world) = self.ctx.world() else {
+            gpu.world = None;
+            return Ok(());
+        };
+        let _p = profile::scope("world_upload");
+        let cam = self.ctx.camera;
+        let zoom = cam.zoom.max(f32::EPSILON);
+        let (vw, vh) = (cam.view_w as f32 / zoom, cam.view_h as f32 / zoom);
+        // room for the view's bounding box at any angle (a stable size while
+        // rotating), plus one spare pixel each way for the fractional position
+        let (tw, th) = if cam.angle == 0.0 { (vw, vh) } else { let d = vw.hypot(vh); (d, d) };
+        let size = (tw.ceil() as u32 + 1, th.ceil() as u32 + 1);
+        let (x, y, _, _) = cam.visible_rect();
+        let view = Rect::new(x.floor() as i32, y.floor() as i32, size.0, size.1);
+        let visible = view.intersect(&Rect::new(0, 0, world.width(), world.height()));
+        let err = |e| PixError::Texture(format!("world upload: {e:?}"));
+        if gpu.world.as_ref().is_none_or(|w| w.size != size) {
+            let ts = TextureSettings::new().filter(Filter::Nearest);
+            let blank = self.ctx.scratch().alloc::<u8>(size.0 as usize * size.1 as usize * 4);
+            let tex = Texture::create(&mut gpu.tex_ctx, Format::Rgba8, blank, [size.0, size.1], &ts).map_err(err)?;
+            gpu.world = Some(WorldTex { tex, size, shown: None });
+        }
+        let wt = gpu.world.as_mut().expect("created above");
+        if !dirty && wt.shown == visible { return Ok(()); }
+        wt.shown = visible;
+        let Some(r) = visible else { return Ok(()) };
+        let n = r.w as usize;
+        let data = self.ctx.scratch().alloc::<[u8; 4]>(n * r.h as usize);
+        for (out, row) in data.chunks_exact_mut(n).zip(world.rows().skip(r.y as usize)) {
+            out.copy_from_slice(&row[r.x as usize..][..n]);
+        }
+        UpdateTexture::update(&mut wt.tex, &mut gpu.tex_ctx, Format::Rgba8, data.as_flattened(), [0, 0], [r.w, r.h])
+            .map_err(err)
+    }
+
+    /// Sharp-bilinear upload: nearest upscale by the integer part of the scale,
+    /// into a linear-filtered texture (recreated when the factor or size changes)
+    fn upload_prescaled(&mut self) -> PixResult<()> {
+        let _p = profile::scope("texture_upload");
+        let (sx, sy) = self.scale_mode.scale(self.window_size, self.canvas, self.pixel_aspect);
+        let k = sx.min(sy).floor().max(1.0) as u32;
+        let fb = if self.post_active { &self.output } else { &self.framebuffer };
+        let (fw, fh) = (fb.w, fb.h);
+        let gpu = self.gpu.as_mut().expect("windowed engine");
+        gpu.shown.invalidate(); // `tex` is not kept current meanwhile
+        let size = (fw * k, fh * k);
+        let stale = gpu.prescaled.as_ref().is_none_or(|(pk, s, _)| *pk != k || *s != size);
+        let img = self.ctx.scratch().alloc::<[u8; 4]>(size.0 as usize * size.1 as usize);
+        for (out, src) in img.chunks_exact_mut(size.0 as usize * k as usize).zip(fb.rows()) {
+            let (row, rest) = out.split_at_mut(size.0 as usize);
+            for (d, s) in row.chunks_exact_mut(k as usize).zip(src) { d.fill(*s); }
+            for r in rest.chunks_exact_mut(size.0 as usize) { r.copy_from_slice(row); }
+        }
+        let bytes = img.as_flattened();
+        if stale {
+            let ts = TextureSettings::new().filter(Filter::Linear);
+            let tex = Texture::create(&mut gpu.tex_ctx, Format::Rgba8, bytes, [size.0, size.1], &ts)
+                .map_err(|e| PixError::Texture(format!("{e:?}")))?;
+            gpu.prescaled = Some((k, size, tex));
+        } else if let Some((_, _, tex)) = gpu.prescaled.as_mut() {
+            UpdateTexture::update(tex, &mut gpu.tex_ctx, Format::Rgba8, bytes, [0, 0], [size.0, size.1])
+                .map_err(|e| PixError::Texture(format!("upload: {e:?}")))?;
+        }
+        Ok(())
+    }
+
+    /// Run the fixed updates owed for `dt` real seconds, then the unscaled update
+    fn update_frame(&mut self, dt: f64) -> PixResult<()> {
+        self.ctx.begin_frame(dt);
+        if !self.focused && self.pause_unfocused {
+            self.acc = 0.0;
+        } else if self.debug.paused {
+            // frame-stepping: only run explicitly requested ticks
+            self.acc = 0.0;
+            let step = self.fixed_dt();
+            for _ in 0..std::mem::take(&mut self.debug.steps) { self.tick(step)?; }
+        } else {
+            let scale = self.ctx.time_scale() * self.scenes.top().time_scale().max(0.0) * self.debug.speed;
+            self.acc += dt * scale;
+        }
+        let update_scope = profile::scope("update");
+        if self.timestep == Timestep::Variable {
+            // whatever scaled time accumulated this frame, in one update
+            let dt = std::mem::take(&mut self.acc);
+            if dt > 0.0 && !self.quit { self.tick(dt)?; }
+        } else {
+            let step = self.fixed_dt();
+            let mut updates = 0;
+            while self.acc >= step && !self.quit {
+                if self.max_updates > 0 && updates == self.max_updates {
+                    // spiral-of-death guard: skip whole steps, keep the remainder
+                    let dropped = self.acc - self.acc % step;
+                    self.acc -= dropped;
+                    self.scenes.top_mut().time_dropped(dropped);
+                    break;
                 }
+                self.tick(step)?;
+                self.acc -= step;
+                updates += 1;
+            }
+        }
+        let alpha = match self.timestep {
+            Timestep::Fixed(_) => self.acc / self.fixed_dt(),
+            Timestep::Variable => 0.0,
+        };
+        self.ctx.set_alpha(alpha);
+        for vp in &mut self.viewports { vp.ctx.set_alpha(alpha); }
+        drop(update_scope);
+        self.scenes.top_mut().update_unscaled(&mut self.ctx);
+        self.apply_commands()?;
+        // the scene may have changed the canvas resolution during update
+        self.sync_canvas_size()
+    }
+
+    /// Scene, layers and viewports into the framebuffer, then the transition and
+    /// post passes (palette cycles, `post_process`, LUT, PostFx, color filter) into `output`
+    fn compose_frame(&mut self) -> PixResult<()> {
+        let camera = self.ctx.camera;
+        if self.smooth_camera && camera.angle == 0.0 {
+            let (fx, fy) = camera.subpixel();
+            self.camera_frac = (fx as f64, fy as f64);
+            self.ctx.camera = camera.snapped();
+        } else {
+            self.camera_frac = (0.0, 0.0);
+        }
+        {
+            let _p = profile::scope("draw");
+            self.scenes.draw(&self.ctx, &mut self.framebuffer);
+        }
+        if !self.layers.is_empty() {
+            let _p = profile::scope("layers");
+            self.layers.clear();
+            self.scenes.top().draw_layers(&mut self.layers);
+            self.layers.composite(&mut self.framebuffer);
+        }
+        self.ctx.camera = camera;
+        if !self.viewports.is_empty() {
+            let _p = profile::scope("viewports");
+            for vp in &mut self.viewports { vp.composite(&mut self.framebuffer); }
+        }
+        self.sync_canvas_size()?; // ... or while drawing
+        self.post_active = self.scenes.is_transitioning() || self.ctx.palette.is_some() || self.post_process.is_some() || self.color_lut.is_some()
+            || !self.post_fx.is_off() || self.color_filter != ColorFilter::None;
+        if self.post_active {
+            self.output.resize(self.framebuffer.w, self.framebuffer.h);
+            self.output.buf.copy_from_slice(&self.framebuffer.buf);
+        }
+        let out = &mut self.output;
+        if self.scenes.is_transitioning() {
+            let _p = profile::scope("transition");
+            let mut assets = Assets { tex_ctx: self.gpu.as_mut().map(|g| &mut g.tex_ctx) };
+            self.scenes.draw_transition(out, &mut assets);
+        }
+        if let Some(pal) = &self.ctx.palette {
+            let _p = profile::scope("palette_cycles");
+            out.apply_palette_cycles(pal);
+        }
+        if let Some(f) = &mut self.post_process {
+            let _p = profile::scope("post_process");
+            f(out);
+        }
+        if let Some((lut, amount)) = &self.color_lut {
+            let _p = profile::scope("color_lut");
+            out.apply_lut_mix(lut, *amount);
+        }
+        if !self.post_fx.is_off() {
+            let _p = profile::scope("post_fx");
+            out.apply_post_fx(&self.post_fx);
+        }
+        if self.color_filter != ColorFilter::None {
+            let _p = profile::scope("color_filter");
+            out.apply_color_filter_in(self.color_filter, self.ctx.scratch());
+        }
+        crash::record_frame();
+        self.ctx.end_frame();
+        Ok(())
+    }
+
+    /// Run until the window closes; returns early on GPU errors. Headless
+    /// engines have no event loop (use `frame` instead).
+    pub fn run(&mut self) -> PixResult<()> {
+        if self.gpu.is_none() {
+            return Err(PixError::Window("headless engine has no window to run; call frame()".into()));
+        }
+        while let Some(e) = self.gpu().window.next() {
+            if self.should_quit() {
+                self.gpu().window.set_should_close(true);
+                break;
+            }
+            if let Some(Button::Keyboard(k)) = e.press_args() {
+                if !self.fullscreen_hotkey(k) && !self.debug_hotkey(k) { self.send_key(k, true); }
             }
+            if let Some(Button::Keyboard(k)) = e.release_args() {
+                self.send_key(k, false);
+            }
+
+            if let Some(Button::Controller(b)) = e.press_args() { self.send_gamepad_button(b.id, b.button, true); }
+            if let Some(Button::Controller(b)) = e.release_args() { self.send_gamepad_button(b.id, b.button, false); }
+            if let Some(a) = e.controller_axis_args() { self.send_gamepad_axis(a.id, a.axis, a.position); }
 
-            // --- If window loses focus, clear keys to avoid “stuck key” bugs
-            if let Some(focused) = e.focus_args() {
-                if !focused { self.pressed.clear(); }
+            if let Some(focused) = e.focus_args() { self.set_focused(focused); }
+            if let Some(r) = e.resize_args() {
+                self.set_window_size(r.window_size[0] as u32, r.window_size[1] as u32);
             }
 
 
             if let Some(u) = e.update_args() {
-                acc += u.dt;
-                while acc >= FIXED_DT {
-                    self.scene.update(FIXED_DT, & mut self.framebuffer);
-                    acc -= FIXED_DT;
+                let start = std::time::Instant::now();
+                self.update_frame(u.dt)?;
+                if self.skip_frame() {
+                    self.ctx.record_skipped_frame();
+                } else {
+                    // draw into pixel buffer, upload CPU → GPU
+                    self.render()?;
+                    self.frame_cost = start.elapsed().as_secs_f64();
                 }
-                // draw into pixel buffer
-                self.scene.draw( & mut self.framebuffer);
-                // upload CPU → GPU
-                self.tex.update( & mut self.tex_ctx, & self.framebuffer.buf).unwrap();
             }
 
-            self.window.draw_2d( & e, | c, g, device | {
+            let (frac_x, frac_y) = if self.smooth_scroll {
+                let (fx, fy) = if self.smooth_camera { self.camera_frac } else { self.scenes.top().subpixel_offset() };
+                (fx.clamp(0.0, 1.0), fy.clamp(0.0, 1.0))
+            } else {
+                (0.0, 0.0)
+            };
+            let smooth = self.smooth_scroll;
+            // scale per ScaleMode (integer keeps pixels crisp), recomputed as the window resizes
+            let [off_x, off_y, draw_w, draw_h] = self.present_rect();
+            let (canvas_w, canvas_h) = self.canvas;
+            let (sx, sy) = (draw_w / canvas_w as f64, draw_h / canvas_h as f64);
+
+            let _p = e.render_args().map(|_| profile::scope("present"));
+            let letterbox = self.letterbox.map(|v| v as f32 / 255.0);
+            let layers = &self.layers;
+            let cam = self.ctx.camera;
+            let Gpu { window, tex_ctx, tex, prescaled, border, world, .. } = self.gpu.as_mut().expect("windowed engine");
+            let tex = prescaled.as_ref().map_or(&*tex, |(_, _, t)| t);
+            window.draw_2d( & e, | c, g, device | {
                 // flush pending texture updates
-                self.tex_ctx.encoder.flush(device);
+                tex_ctx.encoder.flush(device);
 
                 // clear the window framebuffer
-                clear([0.07, 0.07, 0.08, 1.0], g);
-
-                // integer upscale to keep pixels crisp
-                //let [win_w, win_h]: [u32; 2] = e.draw_size().into();
-                let sx = win_w / LOW_W;
-                let sy = win_h / LOW_H;
-                let scale = cmp::min(sx, sy).max(1);
-                let draw_w = (LOW_W * scale) as f64;
-                let draw_h = (LOW_H * scale) as f64;
-                let off_x = ((win_w as f64 - draw_w) * 0.5).floor();
-                let off_y = ((win_h as f64 - draw_h) * 0.5).floor();
-
-                Image::new()
-                    .rect([off_x, off_y, draw_w, draw_h])
-                    .draw( & self.tex, & c.draw_state, c.transform, g);
+                clear(letterbox, g);
+                if let Some((border_tex, (bw, bh))) = border.as_ref() {
+                    let (bw, bh) = (*bw as f64 * sx, *bh as f64 * sy);
+                    Image::new()
+                        .rect([off_x + (draw_w - bw) * 0.5, off_y + (draw_h - bh) * 0.5, bw, bh])
+                        .draw(border_tex, &c.draw_state, c.transform, g);
+                }
+
+                let dpi = c.viewport.map_or(1.0, |v| v.draw_size[0] as f64 / v.window_size[0].max(1.0));
+                let clip = [(off_x * dpi) as u32, (off_y * dpi) as u32, (draw_w * dpi) as u32, (draw_h * dpi) as u32];
+                // streamed world region behind the canvas, through the camera transform
+                if let Some(WorldTex { tex: world_tex, shown: Some(r), .. }) = world.as_ref() {
+                    let (cx, cy) = cam.center();
+                    let world_xf = c.transform.trans(off_x, off_y).scale(sx, sy)
+                        .trans(cam.view_w as f64 * 0.5, cam.view_h as f64 * 0.5)
+                        .rot_rad(-cam.angle as f64)
+                        .zoom(cam.zoom as f64)
+                        .trans(-cx as f64, -cy as f64);
+                    Image::new()
+                        .src_rect([0.0, 0.0, r.w as f64, r.h as f64])
+                        .rect([r.x as f64, r.y as f64, r.w as f64, r.h as f64])
+                        .draw(world_tex, &c.draw_state.scissor(clip), world_xf, g);
+                }
+                if smooth {
+                    // oversized buffer shifted by the fractional camera, cropped to the canvas
+                    Image::new()
+                        .rect([off_x - frac_x * sx, off_y - frac_y * sy, draw_w + sx, draw_h + sy])
+                        .draw( tex, & c.draw_state.scissor(clip), c.transform, g);
+                } else {
+                    Image::new()
+                        .rect([off_x, off_y, draw_w, draw_h])
+                        .draw( tex, & c.draw_state, c.transform, g);
+                }
+
+                // GPU sprite layers, in canvas pixels scaled like the canvas image
+                let canvas_xf = c.transform.trans(off_x - frac_x * sx, off_y - frac_y * sy).scale(sx, sy);
+                let ds = c.draw_state.scissor(clip);
+                for (batch, (ox, oy), opacity) in layers.gpu_layers() {
+                    batch.render(&ds, canvas_xf.trans(-ox as f64, -oy as f64), opacity, g);
+                }
             });
         }
+        Ok(())
     }
 
 }
+
+impl Drop for PixEngine {
+    fn drop(&mut self) {
+        // unwinding from a panic: add the last frame to the crash dump, if any
+        if std::thread::panicking() { crash::write_frame(self.framebuffer()); }
+    }
+}
@@ -0,0 +1,102 @@
+//! -------- LUT: color grading via 3D lookup tables --------
+//!
+//! A `ColorLut` maps every input color to an output color through an N^3 grid,
+//! trilinearly interpolated. Author grades in any image editor by color-
+//! correcting the identity strip (`ColorLut::identity(16).to_strip()`) and
+//! loading the result. Set one via `PixEngine::set_color_lut` for a final pass.
+
+use crate::{PixError, PixResult, PixelBuffer};
+use image::{Rgba, RgbaImage};
+
+/// N x N x N RGB grid, red fastest, then green, then blue
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorLut {
+    size: u32,
+    data: Vec<[u8; 3]>,
+}
+
+impl ColorLut {
+    /// LUT that leaves colors unchanged (`size` >= 2)
+    pub fn identity(size: u32) -> Self {
+        let n = size.max(2);
+        let level = |i: u32| (i * 255 / (n - 1)) as u8;
+        let mut data = Vec::with_capacity((n * n * n) as usize);
+        for b in 0..n {
+            for g in 0..n {
+                for r in 0..n { data.push([level(r), level(g), level(b)]); }
+            }
+        }
+        Self { size: n, data }
+    }
+    /// Decode a strip LUT: N*N x N (blue slices left to right, red across and
+    /// green down each slice) or the same rotated, N x N*N (slices top to bottom)
+    pub fn from_strip(img: &RgbaImage) -> PixResult<Self> {
+        let (w, h) = img.dimensions();
+        let (n, horizontal) = if h >= 2 && w == h * h { (h, true) } else if w >= 2 && h == w * w { (w, false) } else {
+            return Err(PixError::Format(format!("LUT strip must be N*N x N or N x N*N, got {w}x{h}")));
+        };
+        let mut data = Vec::with_capacity((n * n * n) as usize);
+        for b in 0..n {
+            for g in 0..n {
+                for r in 0..n {
+                    let (x, y) = if horizontal { (b * n + r, g) } else { (r, b * n + g) };
+                    let p = img.get_pixel(x, y).0;
+                    data.push([p[0], p[1], p[2]]);
+                }
+            }
+        }
+        Ok(Self { size: n, data })
+    }
+    /// Load a strip LUT PNG (see `from_strip`)
+    pub fn load_strip(path: &str) -> PixResult<Self> {
+        Self::from_strip(&image::open(path)?.to_rgba8())
+    }
+    /// Horizontal strip image, e.g. to save the identity as a grading template
+    pub fn to_strip(&self) -> RgbaImage {
+        let n = self.size;
+        RgbaImage::from_fn(n * n, n, |x, y| {
+            let c = self.data[self.index(x % n, y, x / n)];
+            Rgba([c[0], c[1], c[2], 255])
+        })
+    }
+    #[inline] pub fn size(&self) -> u32 { self.size }
+    #[inline]
+    fn index(&self, r: u32, g: u32, b: u32) -> usize {
+        ((b * self.size + g) * self.size + r) as usize
+    }
+    /// Graded color for `c`
+    pub fn map(&self, c: [u8; 3]) -> [u8; 3] {
+        let m = self.size - 1;
+        // grid cell and 8-bit fraction per channel
+        let axis = |v: u8| {
+            let p = v as u32 * m * 256 / 255;
+            let i = (p >> 8).min(m - 1);
+            (i, i + 1, p - (i << 8))
+        };
+        let (r0, r1, fr) = axis(c[0]);
+        let (g0, g1, fg) = axis(c[1]);
+        let (b0, b1, fb) = axis(c[2]);
+        let lerp = |a: [u32; 3], b: [u32; 3], f: u32| -> [u32; 3] {
+            std::array::from_fn(|i| (a[i] * (256 - f) + b[i] * f + 128) >> 8)
+        };
+        let at = |r, g, b| self.data[self.index(r, g, b)].map(|v| v as u32);
+        let lo = lerp(lerp(at(r0, g0, b0), at(r1, g0, b0), fr), lerp(at(r0, g1, b0), at(r1, g1, b0), fr), fg);
+        let hi = lerp(lerp(at(r0, g0, b1), at(r1, g0, b1), fr), lerp(at(r0, g1, b1), at(r1, g1, b1), fr), fg);
+        lerp(lo, hi, fb).map(|v| v as u8)
+    }
+}
+
+impl PixelBuffer {
+    /// Grade the whole buffer through `lut` (alpha kept)
+    pub fn apply_lut(&mut self, lut: &ColorLut) { self.apply_lut_mix(lut, 255); }
+    /// Grade the whole buffer, mixing `amount`/255 of the graded color over the
+    /// original, for fading between moods (dusk, flashbacks)
+    pub fn apply_lut_mix(&mut self, lut: &ColorLut, amount: u8) {
+        if amount == 0 { return; }
+        let a = amount as u32;
+        self.for_each_pixel(|p| {
+            let g = lut.map([p[0], p[1], p[2]]);
+            for i in 0..3 { p[i] = ((g[i] as u32 * a + p[i] as u32 * (255 - a)) / 255) as u8; }
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! -------- Mask: 1-bit stencil for restricting draws --------
+//!
+//! Build a mask from shapes or sprite alpha, install it with
+//! `PixelBuffer::set_mask`, and every subsequent pixel write only lands where the
+//! mask bit is set (keyhole reveals, spotlights, portals).
+
+/// One bit per pixel, packed into u64 words row by row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    w: u32,
+    h: u32,
+    bits: Vec<u64>,
+}
+
+impl Mask {
+    /// New mask with every bit cleared (nothing drawable)
+    pub fn new(w: u32, h: u32) -> Self {
+        let words = (w as usize * h as usize).div_ceil(64);
+        Self { w, h, bits: vec![0; words] }
+    }
+    #[inline] pub fn width(&self) -> u32 { self.w }
+    #[inline] pub fn height(&self) -> u32 { self.h }
+    /// Set or clear every bit
+    pub fn clear(&mut self, on: bool) {
+        self.bits.fill(if on { u64::MAX } else { 0 });
+    }
+    /// Is (x, y) drawable? Out-of-range is never drawable.
+    #[inline]
+    pub fn get(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as u32 >= self.w || y as u32 >= self.h { return false; }
+        let i = y as usize * self.w as usize + x as usize;
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+    /// Set one bit (clipped)
+    #[inline]
+    pub fn set(&mut self, x: i32, y: i32, on: bool) {
+        if x < 0 || y < 0 || x as u32 >= self.w || y as u32 >= self.h { return; }
+        let i = y as usize * self.w as usize + x as usize;
+        if on { self.bits[i / 64] |= 1 << (i % 64); } else { self.bits[i / 64] &= !(1 << (i % 64)); }
+    }
+    /// Flip every bit ("draw everywhere except the spotlight")
+    pub fn invert(&mut self) {
+        for w in &mut self.bits { *w = !*w; }
+    }
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, on: bool) {
+        for j in y..y + h as i32 {
+            for i in x..x + w as i32 { self.set(i, j, on); }
+        }
+    }
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, r: i32, on: bool) {
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy <= r * r { self.set(cx + dx, cy + dy, on); }
+            }
+        }
+    }
+    /// Set bits where the sprite's alpha is at least `threshold` (cut holes by
+    /// stamping into a cleared mask and calling `invert`)
+    pub fn stamp_alpha(
+        &mut self,
+        x: i32,
+        y: i32,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        threshold: u8,
+    ) {
+        for j in 0..sprite_h as usize {
+            for i in 0..sprite_w as usize {
+                if pixels[j * sprite_w as usize + i][3] >= threshold.max(1) {
+                    self.set(x + i as i32, y + j as i32, true);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! -------- Math: small 2D vector type --------
+
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    #[inline] pub const fn new(x: f32, y: f32) -> Self { Self { x, y } }
+    /// Unit vector pointing at `angle` radians
+    #[inline] pub fn from_angle(angle: f32) -> Self { let (s, c) = angle.sin_cos(); Self::new(c, s) }
+    #[inline] pub fn angle(self) -> f32 { self.y.atan2(self.x) }
+    #[inline] pub fn dot(self, o: Vec2) -> f32 { self.x * o.x + self.y * o.y }
+    #[inline] pub fn length_sq(self) -> f32 { self.dot(self) }
+    #[inline] pub fn length(self) -> f32 { self.length_sq().sqrt() }
+    #[inline] pub fn distance(self, o: Vec2) -> f32 { (o - self).length() }
+    /// Unit-length copy, or zero for a zero vector
+    #[inline]
+    pub fn normalized(self) -> Vec2 {
+        let l = self.length();
+        if l > 0.0 { self / l } else { Vec2::ZERO }
+    }
+    /// Clamp the length to at most `max`
+    #[inline]
+    pub fn truncated(self, max: f32) -> Vec2 {
+        let l2 = self.length_sq();
+        if l2 > max * max { self * (max / l2.sqrt()) } else { self }
+    }
+    /// Rotated 90° counter-clockwise (in y-down screen space: clockwise)
+    #[inline] pub fn perp(self) -> Vec2 { Vec2::new(-self.y, self.x) }
+    #[inline] pub fn rotated(self, angle: f32) -> Vec2 {
+        let (s, c) = angle.sin_cos();
+        Vec2::new(self.x * c - self.y * s, self.x * s + self.y * c)
+    }
+    #[inline] pub fn lerp(self, o: Vec2, t: f32) -> Vec2 { self + (o - self) * t }
+}
+
+impl Add for Vec2 { type Output = Vec2; #[inline] fn add(self, o: Vec2) -> Vec2 { Vec2::new(self.x + o.x, self.y + o.y) } }
+impl Sub for Vec2 { type Output = Vec2; #[inline] fn sub(self, o: Vec2) -> Vec2 { Vec2::new(self.x - o.x, self.y - o.y) } }
+impl Mul<f32> for Vec2 { type Output = Vec2; #[inline] fn mul(self, k: f32) -> Vec2 { Vec2::new(self.x * k, self.y * k) } }
+impl Div<f32> for Vec2 { type Output = Vec2; #[inline] fn div(self, k: f32) -> Vec2 { Vec2::new(self.x / k, self.y / k) } }
+impl Neg for Vec2 { type Output = Vec2; #[inline] fn neg(self) -> Vec2 { Vec2::new(-self.x, -self.y) } }
+impl AddAssign for Vec2 { #[inline] fn add_assign(&mut self, o: Vec2) { self.x += o.x; self.y += o.y; } }
+impl SubAssign for Vec2 { #[inline] fn sub_assign(&mut self, o: Vec2) { self.x -= o.x; self.y -= o.y; } }
+impl MulAssign<f32> for Vec2 { #[inline] fn mul_assign(&mut self, k: f32) { self.x *= k; self.y *= k; } }
+
+impl From<(f32, f32)> for Vec2 { #[inline] fn from((x, y): (f32, f32)) -> Self { Vec2::new(x, y) } }
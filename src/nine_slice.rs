@@ -0,0 +1,79 @@
+//! -------- Nine-slice: scalable panels from one small sprite --------
+//!
+//! The sprite is cut into a 3x3 grid by four margins. Corners are drawn 1:1,
+//! edges stretch (or tile) along one axis and the center along both, so a
+//! 16x16 frame can back a dialog box or button of any size.
+
+use crate::{PixelBuffer, Rect};
+
+/// Margins (in sprite pixels) that cut the sprite into nine cells
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NineSlice {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    /// Repeat edges and center instead of stretching them
+    pub tile: bool,
+}
+
+impl NineSlice {
+    /// Same margin on all four sides, stretched
+    pub fn uniform(m: u32) -> Self {
+        Self { left: m, top: m, right: m, bottom: m, tile: false }
+    }
+}
+
+/// Split `len` into (start, middle, end) lengths, shrinking the margins
+/// proportionally when they don't fit
+fn split(len: u32, a: u32, b: u32) -> (u32, u32, u32) {
+    if a + b <= len { return (a, len - a - b, b); }
+    let a = (len as u64 * a as u64 / (a + b) as u64) as u32;
+    (a, 0, len - a)
+}
+
+impl PixelBuffer {
+    /// Alpha-blit a nine-slice sprite so it exactly covers `dst`
+    pub fn blit_nine_slice(
+        &mut self,
+        sprite_w: u32,
+        sprite_h: u32,
+        pixels: &[[u8; 4]],
+        slice: NineSlice,
+        dst: Rect,
+    ) {
+        if sprite_w == 0 || sprite_h == 0 || dst.is_empty() { return; }
+        let l = slice.left.min(sprite_w);
+        let r = slice.right.min(sprite_w - l);
+        let t = slice.top.min(sprite_h);
+        let b = slice.bottom.min(sprite_h - t);
+        let src_cols = [(0, l), (l, sprite_w - l - r), (sprite_w - r, r)];
+        let src_rows = [(0, t), (t, sprite_h - t - b), (sprite_h - b, b)];
+        let (dl, dm, dr) = split(dst.w, l, r);
+        let (dt, dmid, db) = split(dst.h, t, b);
+        let dst_cols = [(dst.x, dl), (dst.x + dl as i32, dm), (dst.x + (dl + dm) as i32, dr)];
+        let dst_rows = [(dst.y, dt), (dst.y + dt as i32, dmid), (dst.y + (dt + dmid) as i32, db)];
+        for (&(sy, sh), &(dy, dh)) in src_rows.iter().zip(&dst_rows) {
+            for (&(sx, sw), &(dx, dw)) in src_cols.iter().zip(&dst_cols) {
+                let src = Rect::new(sx as i32, sy as i32, sw, sh);
+                self.blit_cell(sprite_w, pixels, src, Rect::new(dx, dy, dw, dh), slice.tile);
+            }
+        }
+    }
+
+    /// Fill `dst` with the `src` region of a sprite, stretched (nearest) or tiled
+    fn blit_cell(&mut self, sprite_w: u32, pixels: &[[u8; 4]], src: Rect, dst: Rect, tile: bool) {
+        if src.is_empty() { return; }
+        let Some(vis) = dst.intersect(&self.bounds()) else { return };
+        let map = |d: i32, dlen: u32, slen: u32| -> i32 {
+            if tile { d % slen as i32 } else { (d as i64 * slen as i64 / dlen as i64) as i32 }
+        };
+        for y in vis.y..vis.bottom() {
+            let v = src.y + map(y - dst.y, dst.h, src.h);
+            for x in vis.x..vis.right() {
+                let u = src.x + map(x - dst.x, dst.w, src.w);
+                self.blend(x as u32, y as u32, pixels[v as usize * sprite_w as usize + u as usize]);
+            }
+        }
+    }
+}
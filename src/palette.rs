@@ -0,0 +1,163 @@
+//! -------- Palette: limited-color output --------
+//!
+//! Render in full color, then crunch the finished frame down to a fixed set of
+//! colors with `PixelBuffer::dither_to_palette`, as the last step before upload.
+//!
+//! Palette cycling: draw with a palette's base colors and register ranges with
+//! `Palette::cycle`. Put the palette in `Context::palette` and the engine
+//! advances it every tick and swaps the colors on screen when composing, so
+//! waterfalls and fire animate without redrawing. Scenes must redraw (not
+//! accumulate) the cycled areas each frame.
+
+use crate::PixelBuffer;
+use std::collections::HashMap;
+use std::ops::Range;
+
+const BAYER4: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// A range of palette entries rotating at `speed` steps per second
+/// (negative = backwards)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorCycle {
+    pub range: Range<usize>,
+    pub speed: f32,
+    phase: f64,
+}
+
+impl ColorCycle {
+    /// Whole steps rotated so far, in `0..range.len()`
+    pub fn offset(&self) -> usize {
+        let n = self.range.len().max(1) as f64;
+        self.phase.floor().rem_euclid(n) as usize
+    }
+}
+
+/// An ordered list of opaque colors, with optional cycling ranges
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    pub colors: Vec<[u8; 4]>,
+    cycles: Vec<ColorCycle>,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<[u8; 4]>) -> Self { Self { colors, cycles: Vec::new() } }
+    /// The 16-color PICO-8 palette
+    pub fn pico8() -> Self {
+        Self::from_hex(&[
+            0x000000, 0x1D2B53, 0x7E2553, 0x008751, 0xAB5236, 0x5F574F, 0xC2C3C7, 0xFFF1E8,
+            0xFF004D, 0xFFA300, 0xFFEC27, 0x00E436, 0x29ADFF, 0x83769C, 0xFF77A8, 0xFFCCAA,
+        ])
+    }
+    /// Four-shade green handheld palette, darkest first
+    pub fn gameboy() -> Self { Self::from_hex(&[0x0F380F, 0x306230, 0x8BAC0F, 0x9BBC0F]) }
+    /// Colors from `0xRRGGBB` values
+    pub fn from_hex(hex: &[u32]) -> Self {
+        Self::new(hex.iter().map(|&c| [(c >> 16) as u8, (c >> 8) as u8, c as u8, 255]).collect())
+    }
+    /// Rotate the entries in `range` by `speed` steps per second. The range is
+    /// clamped to the palette; empty ranges are ignored.
+    pub fn cycle(&mut self, range: Range<usize>, speed: f32) -> &mut Self {
+        let range = range.start.min(self.len())..range.end.min(self.len());
+        if !range.is_empty() { self.cycles.push(ColorCycle { range, speed, phase: 0.0 }); }
+        self
+    }
+    #[inline] pub fn cycles(&self) -> &[ColorCycle] { &self.cycles }
+    #[inline] pub fn cycles_mut(&mut self) -> &mut [ColorCycle] { &mut self.cycles }
+    pub fn clear_cycles(&mut self) { self.cycles.clear(); }
+    /// Advance every cycle by `dt` seconds (the engine does this each tick)
+    pub fn update(&mut self, dt: f64) {
+        for c in &mut self.cycles { c.phase += c.speed as f64 * dt; }
+    }
+    /// Color shown for entry `i` right now, after cycling
+    pub fn current(&self, i: usize) -> [u8; 4] {
+        let mut i = i;
+        // later cycles win where ranges overlap
+        if let Some(c) = self.cycles.iter().rev().find(|c| c.range.contains(&i)) {
+            let n = c.range.len();
+            i = c.range.start + (i - c.range.start + n - c.offset()) % n;
+        }
+        self.colors[i]
+    }
+    #[inline] pub fn len(&self) -> usize { self.colors.len() }
+    #[inline] pub fn is_empty(&self) -> bool { self.colors.is_empty() }
+    /// Index of the closest color (RGB distance); 0 for an empty palette
+    pub fn nearest(&self, c: [i32; 3]) -> usize {
+        let mut best = (0, i32::MAX);
+        for (i, p) in self.colors.iter().enumerate() {
+            let (dr, dg, db) = (c[0] - p[0] as i32, c[1] - p[1] as i32, c[2] - p[2] as i32);
+            let d = dr * dr * 2 + dg * dg * 4 + db * db * 3;
+            if d < best.1 { best = (i, d); }
+        }
+        best.0
+    }
+}
+
+/// How `dither_to_palette` spreads quantization error
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DitherKind {
+    /// Plain nearest color, flat bands
+    None,
+    /// 4x4 ordered threshold: stable frame to frame, the classic crosshatch
+    #[default]
+    Bayer4x4,
+    /// Error diffusion: smoother gradients, but patterns crawl as things move
+    FloydSteinberg,
+}
+
+impl PixelBuffer {
+    /// Swap pixels drawn in a cycling entry's base color for its current color
+    /// (RGB match, alpha kept). Called by the engine for `Context::palette`.
+    pub fn apply_palette_cycles(&mut self, pal: &Palette) {
+        let mut remap = HashMap::new();
+        for c in &pal.cycles {
+            for i in c.range.clone() {
+                let (from, to) = (pal.colors[i], pal.current(i));
+                if from != to { remap.entry([from[0], from[1], from[2]]).or_insert(to); }
+            }
+        }
+        if remap.is_empty() { return; }
+        self.for_each_pixel(|p| {
+            if let Some(to) = remap.get(&[p[0], p[1], p[2]]) { p[..3].copy_from_slice(&to[..3]); }
+        });
+    }
+    /// Replace every pixel with a palette color (alpha kept). Whole buffer;
+    /// ignores clip, mask and blend mode like the other post steps.
+    pub fn dither_to_palette(&mut self, pal: &Palette, kind: DitherKind) {
+        if pal.is_empty() || self.w == 0 { return; }
+        // threshold amplitude ~ distance between palette levels per channel
+        let spread = (256.0 / (pal.len() as f32).cbrt().max(2.0)) as i32;
+        let w = self.w as usize;
+        match kind {
+            DitherKind::None | DitherKind::Bayer4x4 => {
+                self.for_each_row(0..self.h as usize, |y, row| {
+                    for (x, p) in row.chunks_exact_mut(4).enumerate() {
+                        let off = if kind == DitherKind::None { 0 } else { (BAYER4[y & 3][x & 3] * 2 - 15) * spread / 32 };
+                        let c = pal.colors[pal.nearest([p[0] as i32 + off, p[1] as i32 + off, p[2] as i32 + off])];
+                        p[..3].copy_from_slice(&c[..3]);
+                    }
+                });
+            }
+            DitherKind::FloydSteinberg => {
+                // error rows in 1/16 units, padded by one pixel on each side
+                let mut cur = vec![[0i32; 3]; w + 2];
+                let mut next = vec![[0i32; 3]; w + 2];
+                for row in self.as_bytes_mut().chunks_exact_mut(w * 4) {
+                    for (x, p) in row.chunks_exact_mut(4).enumerate() {
+                        let c: [i32; 3] = std::array::from_fn(|k| (p[k] as i32 + cur[x + 1][k] / 16).clamp(0, 255));
+                        let q = pal.colors[pal.nearest(c)];
+                        for k in 0..3 {
+                            let e = c[k] - q[k] as i32;
+                            cur[x + 2][k] += e * 7;
+                            next[x][k] += e * 3;
+                            next[x + 1][k] += e * 5;
+                            next[x + 2][k] += e;
+                        }
+                        p[..3].copy_from_slice(&q[..3]);
+                    }
+                    std::mem::swap(&mut cur, &mut next);
+                    next.iter_mut().for_each(|e| *e = [0; 3]);
+                }
+            }
+        }
+    }
+}
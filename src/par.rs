@@ -0,0 +1,37 @@
+//! -------- Par: band-parallel row processing --------
+//!
+//! Whole-buffer passes (clear, large fills, filters, grading, post effects)
+//! run through `for_each_row`. With the `parallel` feature the rows are split
+//! into horizontal bands and processed on rayon's thread pool; without it the
+//! same closures run serially.
+
+use crate::PixelBuffer;
+use std::ops::Range;
+
+/// Rows per parallel task; keeps scheduling overhead small next to the work
+#[cfg(feature = "parallel")]
+const BAND: usize = 16;
+
+impl PixelBuffer {
+    /// Call `f(y, row)` for each row in `rows` (clamped to the buffer) with the
+    /// row's raw RGBA bytes. Ignores clip, mask and blend mode.
+    pub(crate) fn for_each_row(&mut self, rows: Range<usize>, f: impl Fn(usize, &mut [u8]) + Send + Sync) {
+        let stride = self.w as usize * 4;
+        let rows = rows.start.min(self.h as usize)..rows.end.min(self.h as usize);
+        if stride == 0 || rows.is_empty() { return; }
+        let raw = &mut self.as_bytes_mut()[rows.start * stride..rows.end * stride];
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            raw.par_chunks_mut(stride * BAND).enumerate().for_each(|(b, band)| {
+                for (i, row) in band.chunks_exact_mut(stride).enumerate() { f(rows.start + b * BAND + i, row); }
+            });
+        }
+        #[cfg(not(feature = "parallel"))]
+        for (i, row) in raw.chunks_exact_mut(stride).enumerate() { f(rows.start + i, row); }
+    }
+    /// Call `f` on every pixel's RGBA bytes (see `for_each_row`)
+    pub(crate) fn for_each_pixel(&mut self, f: impl Fn(&mut [u8]) + Send + Sync) {
+        self.for_each_row(0..self.h as usize, |_, row| row.chunks_exact_mut(4).for_each(&f));
+    }
+}
@@ -0,0 +1,203 @@
+//! -------- Players: local multiplayer device assignment --------
+//!
+//! `PlayerSlots` maps input devices (left/right keyboard halves, the whole
+//! keyboard, gamepads) to player indices and tracks per-player actions. Feed it
+//! from `Scene::key_event` / `gamepad_button` / `gamepad_axis`, call `end_tick`
+//! at the end of `Scene::update`, and query `held(player, Action::A)`. Or store
+//! it in `Context::players` and the engine does the feeding and `end_tick`.
+//!
+//! While joining is open, pressing A or Start on an unassigned device claims the
+//! first free slot.
+
+use piston_window::Key;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action { Up, Down, Left, Right, A, B, X, Y, Start }
+
+impl Action {
+    #[inline] fn bit(self) -> u16 { 1 << self as u16 }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Device {
+    /// WASD + Space (A) / LShift (B) / Q (X) / E (Y) / Tab (Start)
+    KeyboardLeft,
+    /// Arrows + Return (A) / RShift (B) / RCtrl (X) / Slash (Y) / Backspace (Start)
+    KeyboardRight,
+    /// Both halves for a single keyboard player
+    Keyboard,
+    Gamepad(u32),
+}
+
+/// Raw gamepad button/axis numbers for each action (backend-specific)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GamepadMap {
+    pub a: u8, pub b: u8, pub x: u8, pub y: u8, pub start: u8,
+    pub dpad_up: u8, pub dpad_down: u8, pub dpad_left: u8, pub dpad_right: u8,
+    /// Left stick axes, treated as a d-pad past 0.5 deflection
+    pub axis_x: u8, pub axis_y: u8,
+}
+
+impl Default for GamepadMap {
+    /// SDL GameController ordering
+    fn default() -> Self {
+        Self { a: 0, b: 1, x: 2, y: 3, start: 6, dpad_up: 11, dpad_down: 12, dpad_left: 13, dpad_right: 14, axis_x: 0, axis_y: 1 }
+    }
+}
+
+/// A device claimed or released a player slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotEvent {
+    Joined { player: usize, device: Device },
+    Left { player: usize, device: Device },
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Slot {
+    device: Option<Device>,
+    held: u16,
+    prev: u16,
+    // actions coming from stick deflection, merged into `held`
+    stick: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlayerSlots {
+    slots: Vec<Slot>,
+    joining: bool,
+    pub gamepad_map: GamepadMap,
+    events: Vec<SlotEvent>,
+}
+
+impl PlayerSlots {
+    pub fn new(max_players: usize) -> Self {
+        Self { slots: vec![Slot::default(); max_players], joining: true, gamepad_map: GamepadMap::default(), events: Vec::new() }
+    }
+    /// Open/close the "press a button to join" phase
+    pub fn set_joining(&mut self, open: bool) { self.joining = open; }
+    #[inline] pub fn is_joining(&self) -> bool { self.joining }
+    #[inline] pub fn max_players(&self) -> usize { self.slots.len() }
+    pub fn device(&self, player: usize) -> Option<Device> { self.slots.get(player)?.device }
+    pub fn player_of(&self, device: Device) -> Option<usize> {
+        self.slots.iter().position(|s| s.device == Some(device))
+    }
+    /// Number of occupied slots
+    pub fn joined(&self) -> usize { self.slots.iter().filter(|s| s.device.is_some()).count() }
+    /// Assign a device directly (skipping the join flow); replaces any previous owner
+    pub fn assign(&mut self, player: usize, device: Device) {
+        if let Some(p) = self.player_of(device) { self.leave(p); }
+        if let Some(slot) = self.slots.get_mut(player) {
+            *slot = Slot { device: Some(device), ..Slot::default() };
+            self.events.push(SlotEvent::Joined { player, device });
+        }
+    }
+    pub fn leave(&mut self, player: usize) {
+        if let Some(slot) = self.slots.get_mut(player) {
+            if let Some(device) = slot.device.take() {
+                *slot = Slot::default();
+                self.events.push(SlotEvent::Left { player, device });
+            }
+        }
+    }
+    /// Joins/leaves since the last call
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, SlotEvent> { self.events.drain(..) }
+
+    #[inline]
+    pub fn held(&self, player: usize, action: Action) -> bool {
+        self.slots.get(player).is_some_and(|s| (s.held | s.stick) & action.bit() != 0)
+    }
+    /// Went down since the previous `end_tick`
+    #[inline]
+    pub fn just_pressed(&self, player: usize, action: Action) -> bool {
+        self.slots.get(player).is_some_and(|s| (s.held | s.stick) & !s.prev & action.bit() != 0)
+    }
+    /// Went up since the previous `end_tick`
+    #[inline]
+    pub fn just_released(&self, player: usize, action: Action) -> bool {
+        self.slots.get(player).is_some_and(|s| !(s.held | s.stick) & s.prev & action.bit() != 0)
+    }
+    /// Latch the current state as "previous" for edge detection (once per update)
+    pub fn end_tick(&mut self) {
+        for s in &mut self.slots { s.prev = s.held | s.stick; }
+    }
+    /// Forget all held actions (e.g. on focus loss)
+    pub fn release_all(&mut self) {
+        for s in &mut self.slots { s.held = 0; s.stick = 0; }
+    }
+
+    pub fn handle_key(&mut self, key: Key, down: bool) {
+        let Some((half, action)) = key_action(key) else { return };
+        // a full-keyboard player receives both halves
+        let device = [half, Device::Keyboard].into_iter().find(|d| self.player_of(*d).is_some());
+        self.route(device.unwrap_or(half), action, down);
+    }
+    pub fn handle_button(&mut self, id: u32, button: u8, down: bool) {
+        let m = self.gamepad_map;
+        let action = match button {
+            b if b == m.a => Action::A,
+            b if b == m.b => Action::B,
+            b if b == m.x => Action::X,
+            b if b == m.y => Action::Y,
+            b if b == m.start => Action::Start,
+            b if b == m.dpad_up => Action::Up,
+            b if b == m.dpad_down => Action::Down,
+            b if b == m.dpad_left => Action::Left,
+            b if b == m.dpad_right => Action::Right,
+            _ => return,
+        };
+        self.route(Device::Gamepad(id), action, down);
+    }
+    pub fn handle_axis(&mut self, id: u32, axis: u8, position: f64) {
+        let m = self.gamepad_map;
+        let (neg, pos) = match axis {
+            a if a == m.axis_x => (Action::Left, Action::Right),
+            a if a == m.axis_y => (Action::Up, Action::Down),
+            _ => return,
+        };
+        let Some(p) = self.player_of(Device::Gamepad(id)) else { return };
+        let s = &mut self.slots[p];
+        s.stick &= !(neg.bit() | pos.bit());
+        if position < -0.5 { s.stick |= neg.bit(); }
+        if position > 0.5 { s.stick |= pos.bit(); }
+    }
+
+    fn route(&mut self, device: Device, action: Action, down: bool) {
+        match self.player_of(device) {
+            Some(p) => {
+                let s = &mut self.slots[p];
+                if down { s.held |= action.bit(); } else { s.held &= !action.bit(); }
+            }
+            None if down && self.joining && matches!(action, Action::A | Action::Start) => {
+                if let Some(free) = self.slots.iter().position(|s| s.device.is_none()) {
+                    self.assign(free, device);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn key_action(key: Key) -> Option<(Device, Action)> {
+    use Device::{KeyboardLeft as L, KeyboardRight as R};
+    Some(match key {
+        Key::W => (L, Action::Up),
+        Key::S => (L, Action::Down),
+        Key::A => (L, Action::Left),
+        Key::D => (L, Action::Right),
+        Key::Space => (L, Action::A),
+        Key::LShift => (L, Action::B),
+        Key::Q => (L, Action::X),
+        Key::E => (L, Action::Y),
+        Key::Tab => (L, Action::Start),
+        Key::Up => (R, Action::Up),
+        Key::Down => (R, Action::Down),
+        Key::Left => (R, Action::Left),
+        Key::Right => (R, Action::Right),
+        Key::Return => (R, Action::A),
+        Key::RShift => (R, Action::B),
+        Key::RCtrl => (R, Action::X),
+        Key::Slash => (R, Action::Y),
+        Key::Backspace => (R, Action::Start),
+        _ => return None,
+    })
+}
@@ -0,0 +1,62 @@
+//! -------- PostFx: retro display effects --------
+//!
+//! A fixed stage the engine runs on the finished canvas each frame (see
+//! `EngineConfig::post_fx` / `PixEngine::set_post_fx`): darkened scanlines,
+//! a vignette and a slight red/blue channel offset. Everything defaults to off.
+
+use crate::PixelBuffer;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PostFx {
+    /// Darken every other canvas row by this much (0 = off, 255 = black)
+    pub scanlines: u8,
+    /// Darken towards the corners; the corners lose this much (0 = off)
+    pub vignette: u8,
+    /// Shift red left and blue right by this many pixels (0 = off)
+    pub chromatic: u32,
+}
+
+impl PostFx {
+    /// A subtle CRT look: light scanlines, soft vignette, 1px fringe
+    pub fn crt() -> Self { Self { scanlines: 64, vignette: 96, chromatic: 1 } }
+    #[inline] pub fn is_off(&self) -> bool { *self == Self::default() }
+}
+
+impl PixelBuffer {
+    /// Run the enabled effects over the whole buffer (alpha kept)
+    pub fn apply_post_fx(&mut self, fx: &PostFx) {
+        let (w, h) = (self.w as usize, self.h as usize);
+        if w == 0 || h == 0 || fx.is_off() { return; }
+        if fx.chromatic > 0 {
+            let d = (fx.chromatic as usize).min(w - 1);
+            self.for_each_row(0..h, |_, row| {
+                // red samples from the right (moves left), blue from the left
+                for x in 0..w - d { row[x * 4] = row[(x + d) * 4]; }
+                for x in (d..w).rev() { row[x * 4 + 2] = row[(x - d) * 4 + 2]; }
+            });
+        }
+        if fx.vignette > 0 {
+            // falls off with squared distance from the centre (in half pixels)
+            let (cx, cy) = (w as i64 - 1, h as i64 - 1);
+            let norm = (cx * cx + cy * cy).max(1);
+            let v = fx.vignette as i64;
+            self.for_each_row(0..h, |y, row| {
+                let dy = 2 * y as i64 - cy;
+                for (x, p) in row.chunks_exact_mut(4).enumerate() {
+                    let dx = 2 * x as i64 - cx;
+                    let keep = 255 - v * (dx * dx + dy * dy) / norm;
+                    for c in &mut p[..3] { *c = (*c as i64 * keep / 255) as u8; }
+                }
+            });
+        }
+        if fx.scanlines > 0 {
+            let keep = 255 - fx.scanlines as u32;
+            self.for_each_row(0..h, |y, row| {
+                if y % 2 == 0 { return; }
+                for p in row.chunks_exact_mut(4) {
+                    for c in &mut p[..3] { *c = (*c as u32 * keep / 255) as u8; }
+                }
+            });
+        }
+    }
+}
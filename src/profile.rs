@@ -0,0 +1,159 @@
+//! -------- Profile: scoped timing markers with trace export --------
+//!
+//! The engine wraps update, draw, layer/viewport compositing, texture upload and
+//! present in scopes; games can add their own with `profile::scope("ai")`.
+//! Recording is off until `start()`; a disabled scope costs two atomic loads.
+//! `save_chrome_trace` writes the Trace Event JSON understood by
+//! chrome://tracing, Perfetto and Tracy's importer. To feed another profiler,
+//! `set_hook` receives every finished scope, and with the `tracing` feature each
+//! scope is also a span for any subscriber: engine markers under their own
+//! names, game scopes as a `pix` span whose `name` field says which.
+
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Hard cap so a forgotten capture can't eat all memory
+const MAX_EVENTS: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub name: &'static str,
+    /// Microseconds since `start()`
+    pub start_us: u64,
+    pub dur_us: u64,
+    pub thread: u32,
+}
+
+struct Recorder {
+    epoch: Instant,
+    events: Vec<Event>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+static NEXT_THREAD: AtomicU32 = AtomicU32::new(1);
+static HOOKED: AtomicBool = AtomicBool::new(false);
+static HOOK: Mutex<Option<Hook>> = Mutex::new(None);
+
+/// Called with each scope's name, start and duration as it ends
+pub type Hook = fn(&'static str, Instant, Duration);
+
+thread_local! {
+    static THREAD_ID: Cell<u32> = const { Cell::new(0) };
+}
+
+fn thread_id() -> u32 {
+    THREAD_ID.with(|id| {
+        if id.get() == 0 { id.set(NEXT_THREAD.fetch_add(1, Ordering::Relaxed)); }
+        id.get()
+    })
+}
+
+/// Begin (or restart) a capture, discarding previous events
+pub fn start() {
+    *RECORDER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Recorder { epoch: Instant::now(), events: Vec::new() });
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Stop recording; captured events stay available for export
+pub fn stop() {
+    ENABLED.store(false, Ordering::Release);
+}
+
+#[inline]
+pub fn is_enabled() -> bool { ENABLED.load(Ordering::Acquire) }
+
+/// Forward every finished scope to `hook` (`None` removes it); runs whether or
+/// not a capture is recording
+pub fn set_hook(hook: Option<Hook>) {
+    *HOOK.lock().unwrap_or_else(|e| e.into_inner()) = hook;
+    HOOKED.store(hook.is_some(), Ordering::Release);
+}
+
+/// RAII marker: the time between creation and drop is recorded under `name`
+#[must_use = "the scope ends when this guard is dropped"]
+pub struct Scope {
+    name: &'static str,
+    start: Option<Instant>,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let Some(start) = self.start else { return };
+        let end = Instant::now();
+        if HOOKED.load(Ordering::Acquire) {
+            let hook = *HOOK.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(hook) = hook { hook(self.name, start, end.duration_since(start)); }
+        }
+        if !is_enabled() { return; }
+        let mut guard = RECORDER.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(rec) = guard.as_mut() else { return };
+        if rec.events.len() >= MAX_EVENTS { return; }
+        rec.events.push(Event {
+            name: self.name,
+            start_us: start.saturating_duration_since(rec.epoch).as_micros() as u64,
+            dur_us: end.duration_since(start).as_micros() as u64,
+            thread: thread_id(),
+        });
+    }
+}
+
+/// Open a timing scope (no-op unless profiling is enabled)
+#[inline]
+pub fn scope(name: &'static str) -> Scope {
+    let timed = is_enabled() || HOOKED.load(Ordering::Acquire);
+    Scope {
+        name,
+        start: timed.then(Instant::now),
+        #[cfg(feature = "tracing")]
+        _span: span(name).entered(),
+    }
+}
+
+/// Span for a scope. `tracing` needs literal span names, so the engine's fixed
+/// markers are spelled out; anything else is a `pix` span with a `name` field.
+#[cfg(feature = "tracing")]
+fn span(name: &'static str) -> tracing::Span {
+    macro_rules! engine_spans {
+        ($($marker:literal),*) => {
+            match name {
+                $($marker => tracing::info_span!($marker),)*
+                _ => tracing::info_span!("pix", name),
+            }
+        };
+    }
+    engine_spans!(
+        "update", "draw", "layers", "viewports", "transition", "palette_cycles", "post_process",
+        "color_lut", "post_fx", "color_filter", "texture_upload", "world_upload", "sprite_batch", "present"
+    )
+}
+
+/// Copy of everything recorded since the last `start()`
+pub fn events() -> Vec<Event> {
+    RECORDER.lock().unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|r| r.events.clone())
+        .unwrap_or_default()
+}
+
+/// Write the capture as Chrome Trace Event JSON
+pub fn save_chrome_trace(path: impl AsRef<Path>) -> io::Result<()> {
+    let mut json = String::from("{\"traceEvents\":[\n");
+    for (i, e) in events().iter().enumerate() {
+        if i > 0 { json.push_str(",\n"); }
+        let _ = write!(
+            json,
+            "{{\"name\":\"{}\",\"cat\":\"pix\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":1,\"tid\":{}}}",
+            e.name.replace('\\', "\\\\").replace('"', "\\\""), e.start_us, e.dur_us, e.thread,
+        );
+    }
+    json.push_str("\n]}\n");
+    std::fs::write(path, json)
+}
@@ -0,0 +1,201 @@
+//! -------- Scenes: stack of active scenes --------
+//!
+//! Only the top scene receives updates and input. A scene asks for a change by
+//! returning it from `Scene::take_scene_change` (typically a field it filled in
+//! during `update`); the engine applies it after that fixed step. Overlay scenes
+//! (`Scene::is_overlay`) let the scene below them keep drawing, e.g. pause menus.
+//! A change can carry a `Transition` the engine animates on the framebuffer.
+
+use crate::{Assets, BlendMode, Context, PixelBuffer, Scene};
+
+/// Scene transition requested by the active scene
+pub enum SceneChange {
+    /// Suspend the current scene and run this one on top
+    Push(Box<dyn Scene>, Transition),
+    /// Drop the current scene and resume the one below (quits if it was the last)
+    Pop(Transition),
+    /// Swap the current scene for this one
+    Replace(Box<dyn Scene>, Transition),
+}
+
+impl SceneChange {
+    pub fn push(scene: impl Scene + 'static) -> Self { SceneChange::Push(Box::new(scene), Transition::None) }
+    pub fn replace(scene: impl Scene + 'static) -> Self { SceneChange::Replace(Box::new(scene), Transition::None) }
+    pub fn pop() -> Self { SceneChange::Pop(Transition::None) }
+    /// Animate the change: `SceneChange::replace(Menu).with(Transition::FadeToBlack { frames: 30 })`
+    pub fn with(self, t: Transition) -> Self {
+        match self {
+            SceneChange::Push(s, _) => SceneChange::Push(s, t),
+            SceneChange::Pop(_) => SceneChange::Pop(t),
+            SceneChange::Replace(s, _) => SceneChange::Replace(s, t),
+        }
+    }
+    fn transition(&self) -> Transition {
+        match self {
+            SceneChange::Push(_, t) | SceneChange::Pop(t) | SceneChange::Replace(_, t) => *t,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WipeDir { #[default] Left, Right, Up, Down }
+
+/// Framebuffer effect played while switching scenes (durations in rendered frames)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transition {
+    #[default]
+    None,
+    /// Old scene fades out to black, then the new one fades in; updates pause during the fade-out
+    FadeToBlack { frames: u32 },
+    /// Last frame of the old scene dissolves into the new scene
+    Crossfade { frames: u32 },
+    /// New scene is revealed by an edge sweeping across the old frame in `dir`
+    Wipe { frames: u32, dir: WipeDir },
+}
+
+struct Running {
+    kind: Transition,
+    frame: u32,
+    /// Old scene's last frame (crossfade / wipe)
+    snapshot: Option<PixelBuffer>,
+    /// Change held back until the fade-out reaches black
+    pending: Option<SceneChange>,
+}
+
+/// Never empty: popping the last scene marks the manager finished instead
+pub struct SceneManager {
+    stack: Vec<Box<dyn Scene>>,
+    finished: bool,
+    running: Option<Running>,
+}
+
+impl SceneManager {
+    pub(crate) fn new(root: Box<dyn Scene>) -> Self {
+        Self { stack: vec![root], finished: false, running: None }
+    }
+    /// True while a fade-out is in progress (the engine holds updates)
+    #[inline] pub fn is_holding(&self) -> bool {
+        self.running.as_ref().is_some_and(|r| r.pending.is_some())
+    }
+    #[inline] pub fn is_transitioning(&self) -> bool { self.running.is_some() }
+    #[inline] pub fn len(&self) -> usize { self.stack.len() }
+    #[inline] pub fn is_empty(&self) -> bool { self.stack.is_empty() }
+    /// True once the root scene popped itself; the engine then stops
+    #[inline] pub fn is_finished(&self) -> bool { self.finished }
+    #[inline] pub fn top(&self) -> &dyn Scene { self.stack[self.stack.len() - 1].as_ref() }
+    #[inline] pub fn top_mut(&mut self) -> &mut dyn Scene {
+        let i = self.stack.len() - 1;
+        self.stack[i].as_mut()
+    }
+
+    /// Tell every stacked scene about a new canvas resolution
+    pub(crate) fn canvas_resized(&mut self, w: u32, h: u32) {
+        for s in &mut self.stack { s.canvas_resized(w, h); }
+    }
+
+    /// Tell every stacked scene about a new window size
+    pub(crate) fn window_resized(&mut self, w: u32, h: u32) {
+        for s in &mut self.stack { s.on_resize(w, h); }
+    }
+
+    /// Start a change; `fb` is the scene canvas as last drawn, before the
+    /// transition and post passes (for snapshots, so they aren't graded twice)
+    pub(crate) fn request(&mut self, change: SceneChange, assets: &mut Assets, fb: &PixelBuffer) {
+        let kind = change.transition();
+        match kind {
+            Transition::None => self.apply(change, assets),
+            Transition::FadeToBlack { .. } => {
+                self.running = Some(Running { kind, frame: 0, snapshot: None, pending: Some(change) });
+            }
+            Transition::Crossfade { .. } | Transition::Wipe { .. } => {
+                self.running = Some(Running { kind, frame: 0, snapshot: Some(fb.clone()), pending: None });
+                self.apply(change, assets);
+            }
+        }
+    }
+
+    /// Apply a change; newly entering scenes get their `on_load` first
+    fn apply(&mut self, change: SceneChange, assets: &mut Assets) {
+        match change {
+            SceneChange::Push(mut s, _) => {
+                s.on_load(assets);
+                self.stack.push(s);
+            }
+            SceneChange::Replace(mut s, _) => {
+                s.on_load(assets);
+                let i = self.stack.len() - 1;
+                self.stack[i] = s;
+            }
+            SceneChange::Pop(_) => {
+                if self.stack.len() > 1 { self.stack.pop(); } else { self.finished = true; }
+            }
+        }
+    }
+
+    /// Composite the running transition over the finished frame and advance it
+    pub(crate) fn draw_transition(&mut self, fb: &mut PixelBuffer, assets: &mut Assets) {
+        let Some(run) = self.running.as_mut() else { return };
+        run.frame += 1;
+        let (w, h) = (fb.width() as i32, fb.height() as i32);
+        let done = match run.kind {
+            Transition::None => true,
+            Transition::FadeToBlack { frames } => {
+                let half = (frames / 2).max(1);
+                // fade out over the first half, in over the second
+                let dark = if run.pending.is_some() {
+                    run.frame.min(half) * 255 / half
+                } else {
+                    255 - (run.frame.saturating_sub(half)).min(half) * 255 / half
+                };
+                let black = [0, 0, 0, 255];
+                for p in fb.as_u32_slice_mut() { *p = u32::from_ne_bytes(BlendMode::Alpha.blend(black, p.to_ne_bytes(), dark as u8)); }
+                if run.frame >= half {
+                    if let Some(change) = run.pending.take() {
+                        self.apply(change, assets);
+                        return;
+                    }
+                }
+                run.frame >= half * 2
+            }
+            Transition::Crossfade { frames } => {
+                let frames = frames.max(1);
+                let keep = 255 - run.frame.min(frames) * 255 / frames;
+                if let Some(old) = &run.snapshot {
+                    if old.width() == fb.width() && old.height() == fb.height() {
+                        for (d, s) in fb.as_u32_slice_mut().iter_mut().zip(old.as_u32_slice()) {
+                            *d = u32::from_ne_bytes(BlendMode::Alpha.blend(s.to_ne_bytes(), d.to_ne_bytes(), keep as u8));
+                        }
+                    }
+                }
+                run.frame >= frames
+            }
+            Transition::Wipe { frames, dir } => {
+                let frames = frames.max(1);
+                let t = run.frame.min(frames) as i32;
+                // region of the old frame that is still covered
+                let (x0, y0, x1, y1) = match dir {
+                    WipeDir::Left => (0, 0, w - w * t / frames as i32, h),
+                    WipeDir::Right => (w * t / frames as i32, 0, w, h),
+                    WipeDir::Up => (0, 0, w, h - h * t / frames as i32),
+                    WipeDir::Down => (0, h * t / frames as i32, w, h),
+                };
+                if let Some(old) = &run.snapshot {
+                    for y in y0..y1.min(old.height() as i32) {
+                        for x in x0..x1.min(old.width() as i32) {
+                            fb.set_px(x as u32, y as u32, old.px(x as u32, y as u32));
+                        }
+                    }
+                }
+                run.frame >= frames
+            }
+        };
+        if done { self.running = None; }
+    }
+
+    /// Draw the top scene, preceded by the scenes it overlays
+    pub(crate) fn draw(&self, ctx: &Context, fb: &mut PixelBuffer) {
+        let mut first = self.stack.len() - 1;
+        while first > 0 && self.stack[first].is_overlay() { first -= 1; }
+        for s in &self.stack[first..] { s.draw(ctx, fb); }
+    }
+}
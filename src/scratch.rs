@@ -0,0 +1,107 @@
+//! -------- Scratch: per-frame bump allocator --------
+//!
+//! Temporary buffers for effects, uploads and scaled blits without a heap
+//! allocation per frame. `Scratch::alloc` hands out zeroed slices that live
+//! until the next `reset`; the engine resets `Context::scratch` at the start of
+//! every frame. When a frame outgrows the arena it chains another chunk, and
+//! the next reset merges everything into one chunk big enough for that frame,
+//! so steady-state frames allocate nothing.
+
+use std::cell::{Cell, RefCell};
+use std::ptr::{self, NonNull};
+
+/// Chunks are `u64` words, so any type with alignment up to 8 fits
+type Word = u64;
+
+/// Zeroed heap block owned through a raw pointer, so slices handed out from it
+/// never alias a reference to the whole block
+struct Chunk {
+    ptr: NonNull<Word>,
+    len: usize,
+}
+
+impl Chunk {
+    fn new(len: usize) -> Self {
+        let words = Box::into_raw(vec![0 as Word; len].into_boxed_slice());
+        Self { ptr: NonNull::new(words.cast()).expect("Box is non-null"), len }
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        // SAFETY: `ptr`/`len` came from `Box::into_raw` in `new` and are freed once
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len)) });
+    }
+}
+
+#[derive(Default)]
+pub struct Scratch {
+    /// Chunk memory never moves while borrowed; only the list of chunks grows
+    chunks: RefCell<Vec<Chunk>>,
+    /// Words used in the last chunk
+    used: Cell<usize>,
+    /// Words requested since the last reset (sizes the merged chunk)
+    total: Cell<usize>,
+}
+
+impl Scratch {
+    pub fn new() -> Self { Self::default() }
+    /// Arena pre-sized to `bytes`
+    pub fn with_capacity(bytes: usize) -> Self {
+        let s = Self::default();
+        s.chunks.borrow_mut().push(Chunk::new(bytes.div_ceil(size_of::<Word>())));
+        s
+    }
+    /// `n` values of `T::default()` valid until the next `reset`.
+    /// Panics if `T` needs more than 8-byte alignment or `n` values don't fit in memory.
+    #[allow(clippy::mut_from_ref)] // each call returns a distinct region of the arena
+    pub fn alloc<T: Copy + Default>(&self, n: usize) -> &mut [T] {
+        assert!(align_of::<T>() <= align_of::<Word>(), "scratch values must be at most 8-byte aligned");
+        let bytes = n.checked_mul(size_of::<T>()).expect("scratch allocation overflows usize");
+        let words = bytes.div_ceil(size_of::<Word>());
+        self.total.set(self.total.get() + words);
+        let mut chunks = self.chunks.borrow_mut();
+        let fits = chunks.last().is_some_and(|c| c.len - self.used.get() >= words);
+        if !fits {
+            // double on growth so a burst of small requests doesn't chain many chunks
+            let cap = chunks.last().map_or(0, |c| c.len * 2).max(words).max(1024);
+            chunks.push(Chunk::new(cap));
+            self.used.set(0);
+        }
+        let start = self.used.get();
+        self.used.set(start + words);
+        let chunk = chunks.last().expect("chunk pushed above");
+        // SAFETY: `start..start + words` lies inside the chunk, is aligned for `T`
+        // and is handed out only once before `reset` (which takes `&mut self`);
+        // chunk memory doesn't move when `chunks` grows. The region may still hold
+        // bytes of an earlier allocation, so every value is written through the
+        // raw pointer before a reference to it exists.
+        unsafe {
+            let first = chunk.ptr.as_ptr().add(start).cast::<T>();
+            for i in 0..n { first.add(i).write(T::default()); }
+            std::slice::from_raw_parts_mut(first, n)
+        }
+    }
+    /// Slice holding a copy of `src`
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_copy<T: Copy + Default>(&self, src: &[T]) -> &mut [T] {
+        let out = self.alloc(src.len());
+        out.copy_from_slice(src);
+        out
+    }
+    /// Release everything handed out; the engine calls this once per frame
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if chunks.len() > 1 {
+            let total = self.total.get();
+            chunks.clear();
+            chunks.push(Chunk::new(total));
+        }
+        self.used.set(0);
+        self.total.set(0);
+    }
+    /// Bytes the arena holds
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(|c| c.len * size_of::<Word>()).sum()
+    }
+}
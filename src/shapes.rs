@@ -0,0 +1,290 @@
+//! -------- Shapes: lines, curves, rectangles, polygons and fills on the PixelBuffer --------
+//!
+//! Opaque writes like `put`: clipped to the buffer and respecting the mask.
+//! Filled shapes sample pixel centers, so shapes sharing an edge don't overlap.
+
+use crate::{simd, PixelBuffer, Rect};
+
+impl PixelBuffer {
+    /// Clipped horizontal run from `x0` to `x1` inclusive
+    pub(crate) fn hspan(&mut self, y: i32, x0: i32, x1: i32, c: [u8; 4]) {
+        if y < 0 || y as u32 >= self.h { return; }
+        let (x0, x1) = (x0.min(x1).max(0), x0.max(x1).min(self.w as i32 - 1));
+        if self.direct() {
+            let b = self.bounds();
+            if y < b.y || y >= b.bottom() { return; }
+            let (x0, x1) = (x0.max(b.x), x1.min(b.right() - 1));
+            if x0 > x1 { return; }
+            let row = y as usize * self.w as usize;
+            self.as_u32_slice_mut()[row + x0 as usize..=row + x1 as usize].fill(u32::from_ne_bytes(c));
+            return;
+        }
+        for x in x0..=x1 {
+            self.write(x as u32, y as u32, c);
+        }
+    }
+
+    /// 1px outline of the `w`x`h` rectangle with its top-left at (x, y)
+    pub fn rect(&mut self, x: i32, y: i32, w: u32, h: u32, c: [u8; 4]) {
+        if w == 0 || h == 0 { return; }
+        let (x1, y1) = (x + w as i32 - 1, y + h as i32 - 1);
+        self.hspan(y, x, x1, c);
+        self.hspan(y1, x, x1, c);
+        for yy in y + 1..y1 {
+            self.put(x, yy, c);
+            self.put(x1, yy, c);
+        }
+    }
+
+    /// Solid `w`x`h` rectangle with its top-left at (x, y)
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, c: [u8; 4]) {
+        if w == 0 || h == 0 { return; }
+        if self.direct() {
+            let Some(r) = Rect::new(x, y, w, h).intersect(&self.bounds()) else { return };
+            let (x0, x1) = (r.x as usize * 4, r.right() as usize * 4);
+            self.for_each_row(r.y as usize..r.bottom() as usize, |_, row| simd::fill_row(&mut row[x0..x1], c));
+            return;
+        }
+        let (y0, y1) = (y.max(0), (y + h as i32).min(self.h as i32));
+        for yy in y0..y1 { self.hspan(yy, x, x + w as i32 - 1, c); }
+    }
+
+    /// Solid rectangle with quarter-circle corners of `radius` (clamped to half
+    /// the shorter side)
+    pub fn fill_round_rect(&mut self, x: i32, y: i32, w: u32, h: u32, radius: u32, c: [u8; 4]) {
+        if w == 0 || h == 0 { return; }
+        let r = radius.min(w / 2).min(h / 2) as f32;
+        for row in 0..h as i32 {
+            // vertical distance from the corner circles' centers, at the pixel center
+            let d = if (row as f32) < r {
+                r - row as f32 - 0.5
+            } else if row as f32 >= h as f32 - r {
+                row as f32 + 0.5 - (h as f32 - r)
+            } else {
+                0.0
+            };
+            let inset = if d > 0.0 { (r - (r * r - d * d).max(0.0).sqrt()).round() as i32 } else { 0 };
+            self.hspan(y + row, x + inset, x + w as i32 - 1 - inset, c);
+        }
+    }
+
+    /// Solid triangle through three (x, y) corners (scanline; any winding)
+    pub fn fill_triangle(&mut self, p0: (i32, i32), p1: (i32, i32), p2: (i32, i32), c: [u8; 4]) {
+        let mut v = [p0, p1, p2].map(|(x, y)| (x as f32, y as f32));
+        v.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let [(ax, ay), (bx, by), (cx, cy)] = v;
+        if ay == cy { return; }
+        let x_at = |px: f32, py: f32, qx: f32, qy: f32, y: f32| px + (qx - px) * (y - py) / (qy - py);
+        let first = (ay.ceil() as i32 - 1).max(0);
+        let last = (cy.ceil() as i32).min(self.h as i32);
+        for y in first..last {
+            let yc = y as f32 + 0.5;
+            if yc < ay || yc >= cy { continue; }
+            let xl = x_at(ax, ay, cx, cy, yc);
+            let xs = if yc < by { x_at(ax, ay, bx, by, yc) } else { x_at(bx, by, cx, cy, yc) };
+            let (l, r) = (xl.min(xs), xl.max(xs));
+            // pixels whose centers fall in [l, r)
+            let (xa, xb) = ((l - 0.5).ceil() as i32, (r - 0.5).ceil() as i32 - 1);
+            if xa <= xb { self.hspan(y, xa, xb, c); }
+        }
+    }
+
+    /// Solid polygon through `points` (closed automatically), convex or concave,
+    /// even-odd rule: self-overlapping parts become holes. Scanline fill with an
+    /// active-edge table.
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], c: [u8; 4]) {
+        let pts: Vec<(f32, f32)> = points.iter().map(|&(x, y)| (x as f32, y as f32)).collect();
+        self.fill_polygon_f(&pts, c);
+    }
+
+    /// `fill_polygon` with sub-pixel corners (pixel (x, y) covers x..x+1, y..y+1)
+    pub(crate) fn fill_polygon_f(&mut self, points: &[(f32, f32)], c: [u8; 4]) {
+        if points.len() < 3 { return; }
+        // (y_top, y_bottom, x at y_top, dx/dy), horizontal edges dropped
+        let mut edges: Vec<(f32, f32, f32, f32)> = Vec::with_capacity(points.len());
+        for (i, &(ax, ay)) in points.iter().enumerate() {
+            let (bx, by) = points[(i + 1) % points.len()];
+            if ay == by { continue; }
+            let ((x0, y0), (x1, y1)) = if ay < by { ((ax, ay), (bx, by)) } else { ((bx, by), (ax, ay)) };
+            edges.push((y0, y1, x0, (x1 - x0) / (y1 - y0)));
+        }
+        if edges.is_empty() { return; }
+        edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let y_min = edges[0].0;
+        let y_max = edges.iter().map(|e| e.1).fold(f32::MIN, f32::max);
+        let first = (y_min.ceil() as i32 - 1).max(0);
+        let last = (y_max.ceil() as i32).min(self.h as i32);
+        let (mut next, mut active, mut xs) = (0, Vec::new(), Vec::new());
+        for y in first..last {
+            let yc = y as f32 + 0.5;
+            while next < edges.len() && edges[next].0 <= yc {
+                active.push(edges[next]);
+                next += 1;
+            }
+            active.retain(|e| e.1 > yc);
+            xs.clear();
+            xs.extend(active.iter().filter(|e| e.0 <= yc).map(|e| e.2 + (yc - e.0) * e.3));
+            xs.sort_by(f32::total_cmp);
+            for pair in xs.chunks_exact(2) {
+                let (xa, xb) = ((pair[0] - 0.5).ceil() as i32, (pair[1] - 0.5).ceil() as i32 - 1);
+                if xa <= xb { self.hspan(y, xa, xb, c); }
+            }
+        }
+    }
+
+    /// Line `width` pixels thick with butt caps: the ends are cut square to the
+    /// segment, exactly covering the end pixels (width <= 1 falls back to `line`)
+    pub fn line_thick(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, width: u32, c: [u8; 4]) {
+        if width <= 1 { return self.line(x0, y0, x1, y1, c); }
+        // pixel centers, extended half a pixel past both ends
+        let (ax, ay, bx, by) = (x0 as f32 + 0.5, y0 as f32 + 0.5, x1 as f32 + 0.5, y1 as f32 + 0.5);
+        let len = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+        let (dx, dy) = if len > 0.0 { ((bx - ax) / len, (by - ay) / len) } else { (1.0, 0.0) };
+        let (ex, ey) = (dx * 0.5, dy * 0.5);
+        let hw = width as f32 * 0.5;
+        let (nx, ny) = (-dy * hw, dx * hw);
+        self.fill_polygon_f(&[
+            (ax - ex + nx, ay - ey + ny),
+            (bx + ex + nx, by + ey + ny),
+            (bx + ex - nx, by + ey - ny),
+            (ax - ex - nx, ay - ey - ny),
+        ], c);
+    }
+
+    /// Anti-aliased line (Xiaolin Wu): each step blends two pixels by coverage,
+    /// scaled by `c`'s alpha. `line` stays the crisp default.
+    pub fn line_aa(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, c: [u8; 4]) {
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (mut ax, mut ay, mut bx, mut by) = (x0 as f32, y0 as f32, x1 as f32, y1 as f32);
+        if steep {
+            std::mem::swap(&mut ax, &mut ay);
+            std::mem::swap(&mut bx, &mut by);
+        }
+        if ax > bx {
+            std::mem::swap(&mut ax, &mut bx);
+            std::mem::swap(&mut ay, &mut by);
+        }
+        let gradient = if bx == ax { 1.0 } else { (by - ay) / (bx - ax) };
+        let plot = |fb: &mut PixelBuffer, x: i32, y: i32, cov: f32| {
+            let (px, py) = if steep { (y, x) } else { (x, y) };
+            fb.plot_aa(px, py, c, cov);
+        };
+        let mut y = ay;
+        for x in ax as i32..=bx as i32 {
+            let (yi, f) = (y.floor(), y - y.floor());
+            plot(self, x, yi as i32, 1.0 - f);
+            if f > 0.0 { plot(self, x, yi as i32 + 1, f); }
+            y += gradient;
+        }
+    }
+    /// Bounds-checked blend of `c` at `coverage` (0..1) of its alpha
+    fn plot_aa(&mut self, x: i32, y: i32, c: [u8; 4], coverage: f32) {
+        if x < 0 || y < 0 || x as u32 >= self.w || y as u32 >= self.h { return; }
+        let a = (c[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+        self.blend(x as u32, y as u32, [c[0], c[1], c[2], a]);
+    }
+
+    /// Quadratic Bézier from `p0` to `p2` pulled toward `p1`
+    pub fn bezier_quad(&mut self, p0: (i32, i32), p1: (i32, i32), p2: (i32, i32), c: [u8; 4]) {
+        // exact degree elevation: same curve as a cubic
+        let f = |p: (i32, i32)| (p.0 as f32, p.1 as f32);
+        let (a, b, d) = (f(p0), f(p1), f(p2));
+        let c1 = (a.0 + (b.0 - a.0) * 2.0 / 3.0, a.1 + (b.1 - a.1) * 2.0 / 3.0);
+        let c2 = (d.0 + (b.0 - d.0) * 2.0 / 3.0, d.1 + (b.1 - d.1) * 2.0 / 3.0);
+        let mut pts = vec![a];
+        flatten_cubic(a, c1, c2, d, 0, &mut pts);
+        self.polyline_f(&pts, c);
+    }
+    /// Cubic Bézier from `p0` to `p3` with control points `p1`, `p2`
+    pub fn bezier_cubic(&mut self, p0: (i32, i32), p1: (i32, i32), p2: (i32, i32), p3: (i32, i32), c: [u8; 4]) {
+        let f = |p: (i32, i32)| (p.0 as f32, p.1 as f32);
+        let mut pts = vec![f(p0)];
+        flatten_cubic(f(p0), f(p1), f(p2), f(p3), 0, &mut pts);
+        self.polyline_f(&pts, c);
+    }
+    fn polyline_f(&mut self, pts: &[(f32, f32)], c: [u8; 4]) {
+        let r = |p: (f32, f32)| (p.0.round() as i32, p.1.round() as i32);
+        for w in pts.windows(2) {
+            let ((x0, y0), (x1, y1)) = (r(w[0]), r(w[1]));
+            self.line(x0, y0, x1, y1, c);
+        }
+    }
+
+    /// Circular arc of radius `r` around (cx, cy). Angles in radians, 0 = +x,
+    /// increasing clockwise on screen (y down); `end < start` sweeps backwards.
+    pub fn arc(&mut self, cx: i32, cy: i32, r: u32, start_angle: f32, end_angle: f32, c: [u8; 4]) {
+        let pts = arc_points(cx as f32, cy as f32, r as f32, start_angle, end_angle);
+        self.polyline_f(&pts, c);
+    }
+    /// Filled pie slice (cooldown wedges, radial menus); a sweep of 2π or more
+    /// fills the whole circle
+    pub fn fill_pie(&mut self, cx: i32, cy: i32, r: u32, start_angle: f32, end_angle: f32, c: [u8; 4]) {
+        // pixel centers: a pie at (cx, cy) is symmetric around that pixel
+        let (fx, fy) = (cx as f32 + 0.5, cy as f32 + 0.5);
+        let full = (end_angle - start_angle).abs() >= std::f32::consts::TAU;
+        let mut pts = arc_points(fx, fy, r as f32 + 0.5, start_angle, end_angle);
+        if !full { pts.push((fx, fy)); }
+        self.fill_polygon_f(&pts, c);
+    }
+
+    /// Bucket fill: replace the 4-connected region of pixels matching the color
+    /// at (x, y) with `c`. Iterative span fill, so large regions can't overflow
+    /// the stack; masked-out pixels act as walls.
+    pub fn flood_fill(&mut self, x: i32, y: i32, c: [u8; 4]) {
+        if x < 0 || y < 0 || x as u32 >= self.w || y as u32 >= self.h { return; }
+        let target = self.px(x as u32, y as u32);
+        if target == c { return; }
+        let w = self.w as i32;
+        let fillable = |fb: &PixelBuffer, x: i32, y: i32| {
+            fb.px(x as u32, y as u32) == target && fb.writable(x as u32, y as u32)
+        };
+        let mut stack = vec![(x, y)];
+        while let Some((sx, sy)) = stack.pop() {
+            if !fillable(self, sx, sy) { continue; }
+            let (mut x0, mut x1) = (sx, sx);
+            while x0 > 0 && fillable(self, x0 - 1, sy) { x0 -= 1; }
+            while x1 < w - 1 && fillable(self, x1 + 1, sy) { x1 += 1; }
+            for xx in x0..=x1 { self.set_px(xx as u32, sy as u32, c); }
+            // one seed per run of fillable pixels above and below
+            for ny in [sy - 1, sy + 1] {
+                if ny < 0 || ny as u32 >= self.h { continue; }
+                let mut in_run = false;
+                for xx in x0..=x1 {
+                    let f = fillable(self, xx, ny);
+                    if f && !in_run { stack.push((xx, ny)); }
+                    in_run = f;
+                }
+            }
+        }
+    }
+}
+
+/// Adaptive subdivision (de Casteljau) until the control points lie within
+/// a quarter pixel of the chord; appends the end points of each flat piece
+fn flatten_cubic(a: (f32, f32), b: (f32, f32), c: (f32, f32), d: (f32, f32), depth: u32, out: &mut Vec<(f32, f32)>) {
+    let dist = |p: (f32, f32)| {
+        let (dx, dy) = (d.0 - a.0, d.1 - a.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len < 1e-3 { ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt() } else { ((p.0 - a.0) * dy - (p.1 - a.1) * dx).abs() / len }
+    };
+    if depth >= 12 || dist(b).max(dist(c)) <= 0.25 {
+        out.push(d);
+        return;
+    }
+    let mid = |p: (f32, f32), q: (f32, f32)| ((p.0 + q.0) * 0.5, (p.1 + q.1) * 0.5);
+    let (ab, bc, cd) = (mid(a, b), mid(b, c), mid(c, d));
+    let (abc, bcd) = (mid(ab, bc), mid(bc, cd));
+    let m = mid(abc, bcd);
+    flatten_cubic(a, ab, abc, m, depth + 1, out);
+    flatten_cubic(m, bcd, cd, d, depth + 1, out);
+}
+
+/// Points along an arc, about one pixel apart
+fn arc_points(cx: f32, cy: f32, r: f32, start: f32, end: f32) -> Vec<(f32, f32)> {
+    let sweep = (end - start).clamp(-std::f32::consts::TAU, std::f32::consts::TAU);
+    let n = ((r * sweep.abs()).ceil() as usize).clamp(1, 4096);
+    (0..=n).map(|i| {
+        let (sin, cos) = (start + sweep * i as f32 / n as f32).sin_cos();
+        (cx + cos * r, cy + sin * r)
+    }).collect()
+}
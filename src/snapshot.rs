@@ -0,0 +1,204 @@
+//! -------- Snapshot: golden-image tests for scenes --------
+//!
+//! Runs a `Scene` headlessly (no window, no GPU) for a number of fixed steps
+//! with scripted key input, then compares the framebuffer against a reference
+//! PNG. Set `PIX_UPDATE_SNAPSHOTS=1` to (re)write the references instead.
+//!
+//! ```ignore
+//! let fb = SnapshotRun::new(320, 180)
+//!     .steps(120)
+//!     .press(10, Key::Right)
+//!     .release(70, Key::Right)
+//!     .run(&mut MyScene::default());
+//! snapshot::assert_matches(&fb, "tests/golden/walk_right.png", 2);
+//! ```
+//!
+//! `Scene::on_load` is not called (it needs a GPU texture context).
+
+use crate::{Context, PixelBuffer, Scene, FIXED_DT};
+use image::{Rgba, RgbaImage};
+use piston_window::Key;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Scripted headless run of a scene
+#[derive(Clone, Debug)]
+pub struct SnapshotRun {
+    w: u32,
+    h: u32,
+    steps: u32,
+    inputs: Vec<(u32, Key, bool)>,
+}
+
+impl SnapshotRun {
+    pub fn new(w: u32, h: u32) -> Self {
+        Self { w, h, steps: 1, inputs: Vec::new() }
+    }
+    /// Number of fixed updates to run before the final draw
+    pub fn steps(mut self, n: u32) -> Self {
+        self.steps = n;
+        self
+    }
+    /// Key goes down just before update number `step` (0-based)
+    pub fn press(mut self, step: u32, key: Key) -> Self {
+        self.inputs.push((step, key, true));
+        self
+    }
+    /// Key goes up just before update number `step`
+    pub fn release(mut self, step: u32, key: Key) -> Self {
+        self.inputs.push((step, key, false));
+        self
+    }
+    /// Run the script and return the drawn framebuffer
+    pub fn run(&self, scene: &mut dyn Scene) -> PixelBuffer {
+        let mut fb = PixelBuffer::new(self.w, self.h);
+        let mut ctx = Context::new(self.w, self.h);
+        for step in 0..self.steps {
+            for &(_, key, down) in self.inputs.iter().filter(|(s, _, _)| *s == step) {
+                let changed = if down { ctx.input.press(key) } else { ctx.input.release(key) };
+                if changed { scene.key_event(key, down); }
+            }
+            ctx.begin_frame(FIXED_DT);
+            ctx.begin_tick(FIXED_DT);
+            scene.update(&mut ctx, &mut fb);
+            ctx.end_tick();
+        }
+        scene.draw(&ctx, &mut fb);
+        if let Some(pal) = &ctx.palette { fb.apply_palette_cycles(pal); }
+        fb
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reference PNG couldn't be read or written
+    Image(PathBuf, image::ImageError),
+    /// Reference has different dimensions than the framebuffer
+    SizeMismatch { expected: (u32, u32), actual: (u32, u32) },
+    /// Pixels differ by more than the tolerance; a diff image was written
+    Mismatch { pixels: usize, diff: PathBuf },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Image(p, e) => write!(f, "snapshot {}: {e}", p.display()),
+            SnapshotError::SizeMismatch { expected, actual } =>
+                write!(f, "snapshot size {}x{} != reference {}x{}", actual.0, actual.1, expected.0, expected.1),
+            SnapshotError::Mismatch { pixels, diff } =>
+                write!(f, "{pixels} pixel(s) differ from reference, see {}", diff.display()),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Compare `fb` with the PNG at `reference`. A pixel matches when every channel
+/// is within `tolerance`. On mismatch `<reference>.diff.png` shows differing
+/// pixels in red over a dimmed copy of the actual frame.
+pub fn compare(fb: &PixelBuffer, reference: impl AsRef<Path>, tolerance: u8) -> Result<(), SnapshotError> {
+    let reference = reference.as_ref();
+    if std::env::var_os("PIX_UPDATE_SNAPSHOTS").is_some() {
+        if let Some(dir) = reference.parent() {
+            // best effort; the save below reports the real error
+            let _ = std::fs::create_dir_all(dir);
+        }
+        return fb.to_image().save(reference).map_err(|e| SnapshotError::Image(reference.to_path_buf(), e));
+    }
+    let expected = image::open(reference)
+        .map_err(|e| SnapshotError::Image(reference.to_path_buf(), e))?
+        .to_rgba8();
+    if expected.dimensions() != (fb.w, fb.h) {
+        return Err(SnapshotError::SizeMismatch { expected: expected.dimensions(), actual: (fb.w, fb.h) });
+    }
+    let mut diff = RgbaImage::new(fb.w, fb.h);
+    let mut bad = 0;
+    for (x, y, a) in fb.to_image().enumerate_pixels() {
+        let e = expected.get_pixel(x, y);
+        let differs = a.0.iter().zip(e.0.iter()).any(|(p, q)| p.abs_diff(*q) > tolerance);
+        let out = if differs {
+            bad += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([a[0] / 3, a[1] / 3, a[2] / 3, 255])
+        };
+        diff.put_pixel(x, y, out);
+    }
+    if bad == 0 { return Ok(()); }
+    let mut diff_path = reference.as_os_str().to_owned();
+    diff_path.push(".diff.png");
+    let diff_path = PathBuf::from(diff_path);
+    diff.save(&diff_path).map_err(|e| SnapshotError::Image(diff_path.clone(), e))?;
+    Err(SnapshotError::Mismatch { pixels: bad, diff: diff_path })
+}
+
+/// `compare` that panics with a readable message, for use inside `#[test]`s
+#[track_caller]
+pub fn assert_matches(fb: &PixelBuffer, reference: impl AsRef<Path>, tolerance: u8) {
+    if let Err(e) = compare(fb, reference, tolerance) {
+        panic!("{e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference PNG of `fb` in a fresh temp directory
+    fn reference(name: &str, fb: &PixelBuffer) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pix-snapshot-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ref.png");
+        fb.to_image().save(&path).unwrap();
+        path
+    }
+
+    fn frame() -> PixelBuffer {
+        let mut fb = PixelBuffer::new(8, 6);
+        fb.clear([20, 40, 60, 255]);
+        fb.put(3, 2, [200, 10, 10, 255]);
+        fb
+    }
+
+    #[test]
+    fn identical_frame_matches() {
+        let fb = frame();
+        let path = reference("identical", &fb);
+        compare(&fb, &path, 0).unwrap();
+        assert!(!path.with_extension("png.diff.png").exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn differing_frame_reports_pixels_and_writes_diff() {
+        let fb = frame();
+        let path = reference("differing", &fb);
+        let mut changed = fb.clone();
+        changed.put(0, 0, [255, 255, 255, 255]);
+        changed.put(7, 5, [23, 40, 60, 255]); // within tolerance 3
+        match compare(&changed, &path, 3) {
+            Err(SnapshotError::Mismatch { pixels, diff }) => {
+                assert_eq!(pixels, 1);
+                let img = image::open(&diff).unwrap().to_rgba8();
+                assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 255]);
+                assert_eq!(img.get_pixel(7, 5).0, [23 / 3, 40 / 3, 60 / 3, 255]);
+            }
+            other => panic!("expected a mismatch, got {other:?}"),
+        }
+        compare(&changed, &path, 255).unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn size_mismatch_is_reported() {
+        let path = reference("size", &frame());
+        match compare(&PixelBuffer::new(6, 8), &path, 255) {
+            Err(SnapshotError::SizeMismatch { expected, actual }) => {
+                assert_eq!(expected, (8, 6));
+                assert_eq!(actual, (6, 8));
+            }
+            other => panic!("expected a size mismatch, got {other:?}"),
+        }
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
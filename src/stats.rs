@@ -0,0 +1,94 @@
+//! -------- Stats: frame rate and frame time readout --------
+//!
+//! Updated by the engine once per drawn frame from the wall clock, so it shows
+//! what was actually achieved (not the configured UPS/FPS). Read it from a
+//! scene through `Context::stats` for an on-screen performance overlay.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Frames kept for the sliding window (~2 s at 60 FPS)
+const WINDOW: usize = 120;
+
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    frame_time: f64,
+    updates: u32,
+    skipped: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    samples: VecDeque<Sample>,
+    last_frame: Option<Instant>,
+    pending_updates: u32,
+    pending_skips: u32,
+    total_frames: u64,
+    total_updates: u64,
+    total_skipped: u64,
+}
+
+impl Stats {
+    pub fn new() -> Self { Self::default() }
+
+    /// Frames per second over the window
+    pub fn fps(&self) -> f64 {
+        let t = self.window_time();
+        if t > 0.0 { self.samples.len() as f64 / t } else { 0.0 }
+    }
+    /// Fixed updates per second over the window
+    pub fn ups(&self) -> f64 {
+        let t = self.window_time();
+        if t > 0.0 { self.samples.iter().map(|s| s.updates as f64).sum::<f64>() / t } else { 0.0 }
+    }
+    /// Shortest frame in the window, seconds
+    pub fn min_frame_time(&self) -> f64 {
+        self.frame_times().fold(None, |m: Option<f64>, t| Some(m.map_or(t, |m| m.min(t)))).unwrap_or(0.0)
+    }
+    /// Longest frame in the window, seconds
+    pub fn max_frame_time(&self) -> f64 { self.frame_times().fold(0.0, f64::max) }
+    pub fn avg_frame_time(&self) -> f64 {
+        if self.samples.is_empty() { 0.0 } else { self.window_time() / self.samples.len() as f64 }
+    }
+    /// Frame times in the window, oldest first, seconds
+    pub fn frame_times(&self) -> impl Iterator<Item = f64> + '_ { self.samples.iter().map(|s| s.frame_time) }
+    /// Count frames per `bucket_ms`-wide bin; the last bin also takes everything slower
+    pub fn histogram(&self, bucket_ms: f64, buckets: usize) -> Vec<u32> {
+        let mut bins = vec![0; buckets];
+        if buckets == 0 || bucket_ms <= 0.0 { return bins; }
+        for t in self.frame_times() {
+            let i = ((t * 1000.0 / bucket_ms) as usize).min(buckets - 1);
+            bins[i] += 1;
+        }
+        bins
+    }
+    /// Frames left undrawn by `FrameSkip` over the window
+    pub fn skipped_frames(&self) -> u32 { self.samples.iter().map(|s| s.skipped).sum() }
+    #[inline] pub fn total_frames(&self) -> u64 { self.total_frames }
+    #[inline] pub fn total_updates(&self) -> u64 { self.total_updates }
+    #[inline] pub fn total_skipped(&self) -> u64 { self.total_skipped }
+
+    fn window_time(&self) -> f64 { self.samples.iter().map(|s| s.frame_time).sum() }
+
+    pub(crate) fn record_update(&mut self) {
+        self.pending_updates += 1;
+        self.total_updates += 1;
+    }
+    pub(crate) fn record_skip(&mut self) {
+        self.pending_skips += 1;
+        self.total_skipped += 1;
+    }
+    pub(crate) fn record_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame.replace(now) {
+            let frame_time = now.duration_since(last).as_secs_f64();
+            if self.samples.len() == WINDOW { self.samples.pop_front(); }
+            self.samples.push_back(Sample {
+                frame_time,
+                updates: std::mem::take(&mut self.pending_updates),
+                skipped: std::mem::take(&mut self.pending_skips),
+            });
+        }
+        self.total_frames += 1;
+    }
+}
@@ -0,0 +1,120 @@
+//! -------- Steering: Reynolds-style movement helpers --------
+//!
+//! Every behavior returns a steering force for an `Agent`. Blend several with
+//! `combine` and feed the result to `Agent::apply`:
+//!
+//! ```ignore
+//! let force = steering::combine(&[
+//!     (1.0, steering::seek(&bat, player.pos)),
+//!     (1.5, steering::separation(&bat, &flock_positions, 12.0)),
+//! ]);
+//! bat.apply(force, dt);
+//! ```
+
+use crate::math::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Agent {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    /// Top speed in pixels per second
+    pub max_speed: f32,
+    /// Largest steering force (acceleration) per second
+    pub max_force: f32,
+}
+
+impl Agent {
+    pub fn new(pos: Vec2, max_speed: f32, max_force: f32) -> Self {
+        Self { pos, vel: Vec2::ZERO, max_speed, max_force }
+    }
+    /// Integrate a steering force over `dt` (clamped to max_force / max_speed)
+    pub fn apply(&mut self, force: Vec2, dt: f32) {
+        self.vel = (self.vel + force.truncated(self.max_force) * dt).truncated(self.max_speed);
+        self.pos += self.vel * dt;
+    }
+    /// Facing angle in radians (0 when standing still)
+    #[inline] pub fn heading(&self) -> f32 { self.vel.angle() }
+}
+
+/// Head straight for `target` at full speed
+pub fn seek(agent: &Agent, target: Vec2) -> Vec2 {
+    (target - agent.pos).normalized() * agent.max_speed - agent.vel
+}
+
+/// Run directly away from `threat`
+pub fn flee(agent: &Agent, threat: Vec2) -> Vec2 {
+    -seek(agent, threat) - agent.vel * 2.0
+}
+
+/// Seek that slows down inside `slow_radius` and stops on the target
+pub fn arrive(agent: &Agent, target: Vec2, slow_radius: f32) -> Vec2 {
+    let to = target - agent.pos;
+    let dist = to.length();
+    if dist <= f32::EPSILON { return -agent.vel; }
+    let speed = if dist < slow_radius { agent.max_speed * dist / slow_radius } else { agent.max_speed };
+    to * (speed / dist) - agent.vel
+}
+
+/// Seek where a moving target will be, estimated from distance / speed
+pub fn pursuit(agent: &Agent, target_pos: Vec2, target_vel: Vec2) -> Vec2 {
+    let lookahead = if agent.max_speed > 0.0 { agent.pos.distance(target_pos) / agent.max_speed } else { 0.0 };
+    seek(agent, target_pos + target_vel * lookahead)
+}
+
+/// Flee from where a pursuer will be
+pub fn evade(agent: &Agent, threat_pos: Vec2, threat_vel: Vec2) -> Vec2 {
+    let lookahead = if agent.max_speed > 0.0 { agent.pos.distance(threat_pos) / agent.max_speed } else { 0.0 };
+    flee(agent, threat_pos + threat_vel * lookahead)
+}
+
+/// Push away from neighbours closer than `radius`, stronger the closer they are.
+/// `neighbours` may include the agent itself (zero-distance entries are skipped).
+pub fn separation(agent: &Agent, neighbours: &[Vec2], radius: f32) -> Vec2 {
+    let mut force = Vec2::ZERO;
+    for &n in neighbours {
+        let away = agent.pos - n;
+        let d = away.length();
+        if d > 0.0 && d < radius {
+            force += away / d * ((radius - d) / radius);
+        }
+    }
+    force * agent.max_force
+}
+
+/// State for `wander`: the current point on the wander circle plus a tiny RNG
+#[derive(Clone, Copy, Debug)]
+pub struct Wander {
+    /// How far ahead of the agent the circle sits
+    pub distance: f32,
+    pub radius: f32,
+    /// Max angle change per call, radians
+    pub jitter: f32,
+    angle: f32,
+    seed: u32,
+}
+
+impl Wander {
+    pub fn new(distance: f32, radius: f32, jitter: f32, seed: u32) -> Self {
+        Self { distance, radius, jitter, angle: 0.0, seed: seed.max(1) }
+    }
+    fn next_unit(&mut self) -> f32 {
+        // xorshift32 -> -1.0..1.0
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+/// Meander by steering toward a point jittering around a circle ahead of the agent
+pub fn wander(agent: &Agent, state: &mut Wander) -> Vec2 {
+    state.angle += state.next_unit() * state.jitter;
+    let forward = if agent.vel.length_sq() > 0.0 { agent.vel.normalized() } else { Vec2::new(1.0, 0.0) };
+    let target = agent.pos + forward * state.distance + Vec2::from_angle(forward.angle() + state.angle) * state.radius;
+    seek(agent, target)
+}
+
+/// Weighted sum of steering forces
+pub fn combine(forces: &[(f32, Vec2)]) -> Vec2 {
+    forces.iter().fold(Vec2::ZERO, |acc, &(w, f)| acc + f * w)
+}
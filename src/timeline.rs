@@ -0,0 +1,144 @@
+//! -------- Timeline: time-sequenced cutscene tracks --------
+//!
+//! A `Timeline` holds named tracks (camera, actors, dialog, sfx, fx ...), each a
+//! list of clips with a start time, a duration and a user payload `E`. Drive it
+//! from `Scene::update` with the fixed `dt` and react to the cues it emits.
+
+/// What happened to a clip during `Timeline::update`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cue<'a, E> {
+    /// Clip became active this tick
+    Start(&'a E),
+    /// Clip is active; normalized progress in 0.0..=1.0 (for tweening moves/fades)
+    Tick(&'a E, f32),
+    /// Clip finished (always preceded by a final `Tick(_, 1.0)`)
+    End(&'a E),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClipState { Pending, Active, Done }
+
+#[derive(Debug, Clone)]
+struct Clip<E> {
+    start: f64,
+    duration: f64,
+    event: E,
+    state: ClipState,
+}
+
+/// One lane of clips, e.g. "camera" or "dialog"
+#[derive(Debug, Clone)]
+pub struct Track<E> {
+    name: String,
+    clips: Vec<Clip<E>>,
+}
+
+impl<E> Track<E> {
+    #[inline] pub fn name(&self) -> &str { &self.name }
+    /// Add a clip starting at `start` seconds lasting `duration` seconds (0 = instant)
+    pub fn clip(&mut self, start: f64, duration: f64, event: E) -> &mut Self {
+        let clip = Clip { start, duration: duration.max(0.0), event, state: ClipState::Pending };
+        // keep clips ordered by start so cues fire in time order
+        let at = self.clips.partition_point(|c| c.start <= start);
+        self.clips.insert(at, clip);
+        self
+    }
+    fn end_time(&self) -> f64 {
+        self.clips.iter().map(|c| c.start + c.duration).fold(0.0, f64::max)
+    }
+}
+
+/// Sequencer for scripted intros, endings and in-game cutscenes
+#[derive(Debug, Clone)]
+pub struct Timeline<E> {
+    tracks: Vec<Track<E>>,
+    time: f64,
+    paused: bool,
+    skippable: bool,
+}
+
+impl<E> Default for Timeline<E> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<E> Timeline<E> {
+    pub fn new() -> Self {
+        Self { tracks: Vec::new(), time: 0.0, paused: false, skippable: true }
+    }
+    /// Get (or create) the track with the given name
+    pub fn track(&mut self, name: &str) -> &mut Track<E> {
+        let idx = match self.tracks.iter().position(|t| t.name == name) {
+            Some(i) => i,
+            None => {
+                self.tracks.push(Track { name: name.to_string(), clips: Vec::new() });
+                self.tracks.len() - 1
+            }
+        };
+        &mut self.tracks[idx]
+    }
+    #[inline] pub fn time(&self) -> f64 { self.time }
+    /// Time at which the last clip ends
+    pub fn duration(&self) -> f64 {
+        self.tracks.iter().map(Track::end_time).fold(0.0, f64::max)
+    }
+    pub fn is_finished(&self) -> bool {
+        self.tracks.iter().all(|t| t.clips.iter().all(|c| c.state == ClipState::Done))
+    }
+    /// Hold the playhead (e.g. while a dialog line waits for a button press)
+    pub fn set_paused(&mut self, paused: bool) { self.paused = paused; }
+    #[inline] pub fn is_paused(&self) -> bool { self.paused }
+    /// Allow/deny `skip` (for unskippable story beats)
+    pub fn set_skippable(&mut self, skippable: bool) { self.skippable = skippable; }
+    /// Rewind to the start; all clips become pending again
+    pub fn reset(&mut self) {
+        self.time = 0.0;
+        self.paused = false;
+        for c in self.tracks.iter_mut().flat_map(|t| t.clips.iter_mut()) {
+            c.state = ClipState::Pending;
+        }
+    }
+
+    /// Advance by `dt` (pass the fixed step) and emit cues as `(track_name, cue)`
+    pub fn update(&mut self, dt: f64, mut on_cue: impl FnMut(&str, Cue<'_, E>)) {
+        if self.paused { return; }
+        self.time += dt;
+        let now = self.time;
+        for track in &mut self.tracks {
+            for clip in &mut track.clips {
+                if clip.state == ClipState::Pending && now >= clip.start {
+                    clip.state = ClipState::Active;
+                    on_cue(&track.name, Cue::Start(&clip.event));
+                }
+                if clip.state != ClipState::Active { continue; }
+                let t = if clip.duration > 0.0 { ((now - clip.start) / clip.duration).min(1.0) } else { 1.0 };
+                on_cue(&track.name, Cue::Tick(&clip.event, t as f32));
+                if now >= clip.start + clip.duration {
+                    clip.state = ClipState::Done;
+                    on_cue(&track.name, Cue::End(&clip.event));
+                }
+            }
+        }
+    }
+
+    /// Jump to the end, emitting Start/Tick(1.0)/End for every unfinished clip so
+    /// the game ends up in the same state as if the cutscene had played out.
+    /// Returns false if the timeline is unskippable.
+    pub fn skip(&mut self, mut on_cue: impl FnMut(&str, Cue<'_, E>)) -> bool {
+        if !self.skippable { return false; }
+        self.time = self.time.max(self.duration());
+        self.paused = false;
+        for track in &mut self.tracks {
+            for clip in &mut track.clips {
+                if clip.state == ClipState::Pending {
+                    on_cue(&track.name, Cue::Start(&clip.event));
+                }
+                if clip.state != ClipState::Done {
+                    clip.state = ClipState::Done;
+                    on_cue(&track.name, Cue::Tick(&clip.event, 1.0));
+                    on_cue(&track.name, Cue::End(&clip.event));
+                }
+            }
+        }
+        true
+    }
+}
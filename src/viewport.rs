@@ -0,0 +1,70 @@
+//! -------- Viewports: picture-in-picture scenes --------
+//!
+//! A viewport owns a secondary `Scene` and its own `PixelBuffer`. The engine
+//! updates it on the fixed step alongside the main scene and composites it into
+//! the main framebuffer after the main scene has drawn (security cams, mirrors,
+//! live character portraits).
+
+use crate::{Context, PixelBuffer, Scene};
+
+/// Handle returned by `PixEngine::add_viewport`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ViewportId(pub(crate) u32);
+
+pub struct Viewport {
+    pub(crate) id: ViewportId,
+    pub(crate) scene: Box<dyn Scene>,
+    pub(crate) buffer: PixelBuffer,
+    /// The secondary scene's own clock, input and camera
+    pub(crate) ctx: Context,
+    /// Top-left corner in the main framebuffer
+    pub x: i32,
+    pub y: i32,
+    /// Hidden viewports are neither updated nor drawn
+    pub visible: bool,
+    /// Optional 1px frame drawn around the viewport
+    pub border: Option<[u8; 4]>,
+}
+
+impl Viewport {
+    pub(crate) fn new(id: ViewportId, x: i32, y: i32, w: u32, h: u32, scene: Box<dyn Scene>) -> Self {
+        Self { id, scene, buffer: PixelBuffer::new(w, h), ctx: Context::new(w, h), x, y, visible: true, border: None }
+    }
+    #[inline] pub fn id(&self) -> ViewportId { self.id }
+    #[inline] pub fn width(&self) -> u32 { self.buffer.width() }
+    #[inline] pub fn height(&self) -> u32 { self.buffer.height() }
+    /// Move/resize; the viewport's buffer is reallocated if the size changes
+    pub fn set_rect(&mut self, x: i32, y: i32, w: u32, h: u32) {
+        self.x = x;
+        self.y = y;
+        if w != self.buffer.width() || h != self.buffer.height() {
+            self.buffer = PixelBuffer::new(w, h);
+            self.ctx.sync_canvas_size(w, h);
+        }
+    }
+    pub fn scene_mut(&mut self) -> &mut dyn Scene { self.scene.as_mut() }
+    pub fn context_mut(&mut self) -> &mut Context { &mut self.ctx }
+
+    pub(crate) fn update(&mut self, dt: f64) {
+        if !self.visible { return; }
+        self.ctx.begin_tick(dt);
+        self.scene.update(&mut self.ctx, &mut self.buffer);
+        self.ctx.end_tick();
+    }
+    /// Draw the secondary scene and copy it into `target`
+    pub(crate) fn composite(&mut self, target: &mut PixelBuffer) {
+        if !self.visible { return; }
+        self.scene.draw(&self.ctx, &mut self.buffer);
+        if let Some(pal) = &self.ctx.palette { self.buffer.apply_palette_cycles(pal); }
+        self.ctx.end_frame();
+        target.copy_from(&self.buffer, self.x, self.y);
+        if let Some(c) = self.border {
+            let (x0, y0) = (self.x - 1, self.y - 1);
+            let (x1, y1) = (self.x + self.width() as i32, self.y + self.height() as i32);
+            target.line(x0, y0, x1, y0, c);
+            target.line(x0, y1, x1, y1, c);
+            target.line(x0, y0, x0, y1, c);
+            target.line(x1, y0, x1, y1, c);
+        }
+    }
+}